@@ -1,11 +1,22 @@
 use teloxide::prelude::*;
-use super::keyboards;
-use super::state::{BotState, NodeType};
-use crate::chains::{get_chain_by_id, get_default_chains, Chain};
-use crate::config::Protocol;
-use crate::shodan::ShodanResult;
-use crate::validator::ValidatedNode;
-use futures::future::join_all;
+use super::{commands, keyboards};
+use super::state::{BotState, SearchReport};
+use node_finder::chains::{get_chain_by_id, get_chains, Chain};
+use node_finder::config::{Protocol, WatchStatus, WatchedNode};
+use node_finder::discovery::import::ImportedList;
+use node_finder::discovery::hosting::HOSTING_PROVIDERS;
+use node_finder::discovery::{Discovery, DiscoveryQuery};
+use node_finder::error::Error;
+use node_finder::search::{NodeType, SearchDiagnostics, SearchOutcome, SearchRequest};
+use node_finder::shodan::client::{ShodanFacets, DEFAULT_FACETS};
+use node_finder::store;
+use node_finder::validator::client::ClientFamily;
+use node_finder::validator::tls::TlsInfo;
+use node_finder::validator::ValidatedNode;
+use std::sync::Arc;
+
+/// Imported lists with more working nodes than this are sent as a bulk export
+const MAX_LISTED_IMPORT_RESULTS: usize = 20;
 
 pub async fn handle_callback(
     bot: Bot,
@@ -25,11 +36,8 @@ pub async fn handle_callback(
     match parts.as_slice() {
         // Node type selection
         ["node", node_type] => {
-            let nt = match *node_type {
-                "full" => NodeType::Full,
-                "archive" => NodeType::Archive,
-                "bulk" => NodeType::Bulk,
-                _ => return Ok(()),
+            let Some(nt) = NodeType::from_code(node_type) else {
+                return Ok(());
             };
 
             state.update_session(user_id, |s| {
@@ -59,30 +67,41 @@ pub async fn handle_callback(
                     .await?;
                 }
             } else if let Ok(id) = chain_id.parse::<u64>() {
-                if let Some(chain) = get_chain_by_id(id) {
-                    state.update_session(user_id, |s| {
-                        s.chain = Some(chain);
-                    }).await;
+                let config = state.config_manager.get_user_config(user_id).await;
+                if let Some(chain) = config.get_chain(id) {
+                    if !chain.genesis_hash.is_empty() {
+                        let session = state.get_session(user_id).await;
+                        state.update_session(user_id, |s| {
+                            s.chain = Some(chain);
+                        }).await;
 
-                    if let Some(msg_id) = message_id {
-                        bot.edit_message_text(chat_id, msg_id, "Select a location:")
-                            .reply_markup(keyboards::location_selection())
-                            .await?;
+                        if let Some(msg_id) = message_id {
+                            bot.edit_message_text(chat_id, msg_id, "Select a location:")
+                                .reply_markup(keyboards::location_selection(
+                                    session.imported.is_some(),
+                                    state.shodan.is_some(),
+                                ))
+                                .await?;
+                        }
+                    } else {
+                        commands::confirm_new_genesis(&bot, chat_id, &state, user_id, chain).await?;
                     }
                 }
             }
         }
 
-        // Location selection - trigger search
+        // Location selection - trigger search, or validate an uploaded list
         ["location", location] => {
             let session = state.get_session(user_id).await;
-            let country_code = if *location == "all" { None } else { Some(*location) };
+            let imported = session.imported.filter(|_| *location == "import");
+            let country_code = if *location == "all" || *location == "import" { None } else { Some(*location) };
 
             if let (Some(node_type), Some(chain)) = (session.node_type, session.chain) {
                 // Clear session
                 state.clear_session(user_id).await;
 
                 let chain_name = chain.name.clone();
+                let chain_id = chain.id;
 
                 // Send searching message
                 if let Some(msg_id) = message_id {
@@ -90,6 +109,11 @@ pub async fn handle_callback(
                         .await?;
                 }
 
+                // Known-good nodes from earlier searches while the fresh search runs
+                if imported.is_none() {
+                    send_known_nodes(&bot, chat_id, &state, user_id, node_type, &chain, country_code).await?;
+                }
+
                 // Perform search and validation
                 let result = perform_search(
                     state.clone(),
@@ -97,56 +121,219 @@ pub async fn handle_callback(
                     node_type,
                     chain.clone(),
                     country_code,
+                    imported.clone(),
                 )
                 .await;
 
-                match result {
-                    Ok(nodes) if !nodes.is_empty() => {
-                        send_results(&bot, chat_id, &nodes, node_type, &chain_name).await?;
+                let outcome = match result {
+                    Ok(outcome) => outcome,
+                    Err(e) => {
+                        bot.send_message(chat_id, format!("❌ Error: {}", e)).await?;
+                        return Ok(());
                     }
-                    Ok(_) => {
-                        // No nodes found, try all locations if we had a specific location
-                        if country_code.is_some() {
-                            if let Some(msg_id) = message_id {
-                                bot.edit_message_text(
-                                    chat_id,
-                                    msg_id,
-                                    "No nodes found in selected location. Expanding to all locations...",
-                                )
-                                .await?;
-                            }
-
-                            let expanded_result = perform_search(
-                                state.clone(),
-                                user_id,
-                                node_type,
-                                chain,
-                                None,
-                            )
-                            .await;
-
-                            match expanded_result {
-                                Ok(nodes) if !nodes.is_empty() => {
-                                    send_results(&bot, chat_id, &nodes, node_type, &chain_name).await?;
-                                }
-                                _ => {
-                                    bot.send_message(
-                                        chat_id,
-                                        "❌ No working nodes found. The network may be experiencing issues.",
-                                    )
-                                    .await?;
-                                }
-                            }
-                        } else {
-                            bot.send_message(
-                                chat_id,
-                                "❌ No working nodes found. The network may be experiencing issues.",
-                            )
-                            .await?;
+                };
+
+                // No nodes found, try all locations if we had a specific location
+                let outcome = if outcome.nodes.is_empty() && country_code.is_some() {
+                    if let Some(msg_id) = message_id {
+                        bot.edit_message_text(
+                            chat_id,
+                            msg_id,
+                            "No nodes found in selected location. Expanding to all locations...",
+                        )
+                        .await?;
+                    }
+
+                    match perform_search(state.clone(), user_id, node_type, chain, None, None).await {
+                        Ok(expanded) => expanded,
+                        Err(e) => {
+                            bot.send_message(chat_id, format!("❌ Error: {}", e)).await?;
+                            return Ok(());
                         }
                     }
-                    Err(e) => {
-                        bot.send_message(chat_id, format!("❌ Error: {}", e)).await?;
+                } else {
+                    outcome
+                };
+
+                let report_id = state
+                    .store_report(SearchReport {
+                        chain_id,
+                        urls: outcome.nodes.iter().map(|n| n.url.clone()).collect(),
+                        diagnostics: outcome.diagnostics,
+                    })
+                    .await;
+
+                if outcome.nodes.is_empty() {
+                    let msg = if imported.is_some() {
+                        "❌ None of the imported candidates is a working node for this chain."
+                    } else {
+                        "❌ No working nodes found. The network may be experiencing issues."
+                    };
+                    bot.send_message(chat_id, msg)
+                        .reply_markup(keyboards::search_details(report_id))
+                        .await?;
+                } else {
+                    // A whole imported list does not fit in one message
+                    let display_type = if imported.is_some() && outcome.nodes.len() > MAX_LISTED_IMPORT_RESULTS {
+                        NodeType::Bulk
+                    } else {
+                        node_type
+                    };
+                    send_results(&bot, chat_id, &outcome.nodes, display_type, &chain_name, report_id).await?;
+                }
+            }
+        }
+
+        // Shodan match counts per country and provider, before picking a location
+        ["facets", "show"] => {
+            let session = state.get_session(user_id).await;
+            let (Some(shodan), Some(chain)) = (&state.shodan, session.chain) else {
+                return Ok(());
+            };
+            let config = state.config_manager.get_user_config(user_id).await;
+
+            let query = DiscoveryQuery {
+                chain_id: chain.id,
+                country_code: None,
+                min_results: 0,
+                max_pages: 0,
+                shodan: config.get_shodan_query(chain.id),
+            };
+            let text = match shodan.facets(&query, &DEFAULT_FACETS).await {
+                Ok(facets) => format_facets(&chain.name, &facets),
+                Err(e) => format!(
+                    "❌ Could not fetch Shodan counts: {}\n\nSelect a location:",
+                    html_escape(&e.to_string())
+                ),
+            };
+
+            if let Some(msg_id) = message_id {
+                bot.edit_message_text(chat_id, msg_id, text)
+                    .parse_mode(teloxide::types::ParseMode::Html)
+                    .reply_markup(keyboards::location_selection(session.imported.is_some(), true))
+                    .await?;
+            }
+        }
+
+        // Validation funnel report for an earlier search
+        ["details", id] => {
+            let report = match id.parse::<u64>() {
+                Ok(id) => state.get_report(id).await,
+                Err(_) => None,
+            };
+
+            let msg = match report {
+                Some(report) => format_diagnostics(&report.diagnostics),
+                None => "This report has expired. Run the search again for fresh details.".to_string(),
+            };
+
+            bot.send_message(chat_id, msg)
+                .parse_mode(teloxide::types::ParseMode::Html)
+                .await?;
+        }
+
+        // Pin a node from a results message to the user's watchlist
+        ["watch", report_id, index] => {
+            let report = match report_id.parse::<u64>() {
+                Ok(id) => state.get_report(id).await,
+                Err(_) => None,
+            };
+            let Some(report) = report else {
+                bot.send_message(chat_id, "These results have expired. Run the search again to pin nodes.")
+                    .await?;
+                return Ok(());
+            };
+            let Some(url) = index.parse::<usize>().ok().and_then(|i| report.urls.get(i)).cloned() else {
+                return Ok(());
+            };
+
+            let mut added = false;
+            state.config_manager.update_user_config(user_id, |c| {
+                if !c.is_watching(report.chain_id, &url) {
+                    let now = store::now();
+                    c.watchlist.push(WatchedNode {
+                        url: url.clone(),
+                        chain_id: report.chain_id,
+                        added_at: now,
+                        status: WatchStatus::Up,
+                        status_since: now,
+                        last_checked: now,
+                    });
+                    added = true;
+                }
+            }).await;
+
+            let msg = if added {
+                format!("📌 Watching <code>{}</code>. You'll get an alert if it goes down, falls out of sync or changes chain.", html_escape(&url))
+            } else {
+                format!("Already watching <code>{}</code>.", html_escape(&url))
+            };
+            bot.send_message(chat_id, msg)
+                .parse_mode(teloxide::types::ParseMode::Html)
+                .await?;
+        }
+
+        // Remove a node from the watchlist, by its chain ID and URL hash so a
+        // button on an outdated list never removes a different node
+        ["unwatch", chain_id, url_hash] => {
+            let key = format!("{}:{}", chain_id, url_hash);
+            state.config_manager.update_user_config(user_id, |c| {
+                c.watchlist.retain(|w| w.key() != key);
+            }).await;
+
+            let config = state.config_manager.get_user_config(user_id).await;
+            if let Some(msg_id) = message_id {
+                bot.edit_message_text(chat_id, msg_id, format_watchlist(&config.watchlist))
+                    .parse_mode(teloxide::types::ParseMode::Html)
+                    .reply_markup(keyboards::watchlist(&config.watchlist))
+                    .await?;
+            }
+        }
+
+        // Custom chain genesis confirmation
+        ["genesis", action] => {
+            let session = state.get_session(user_id).await;
+            let Some(chain) = session.pending_chain else {
+                return Ok(());
+            };
+
+            match *action {
+                "confirm" => {
+                    let pinned = chain.clone();
+                    // Registry chains keep their default and fallback RPCs
+                    let is_registry_chain = get_chain_by_id(pinned.id).is_some();
+                    state.config_manager.update_user_config(user_id, |c| {
+                        if !is_registry_chain {
+                            c.reference_rpcs.insert(pinned.id, pinned.reference_rpcs());
+                        }
+                        c.custom_chains.insert(pinned.id, pinned);
+                    }).await;
+
+                    state.update_session(user_id, |s| {
+                        s.pending_chain = None;
+                        s.chain = Some(chain);
+                    }).await;
+
+                    if let Some(msg_id) = message_id {
+                        bot.edit_message_text(chat_id, msg_id, "✅ Custom chain configured!\n\nSelect a location:")
+                            .reply_markup(keyboards::location_selection(
+                                session.imported.is_some(),
+                                state.shodan.is_some(),
+                            ))
+                            .await?;
+                    }
+                }
+                _ => {
+                    state.clear_session(user_id).await;
+                    if let Some(msg_id) = message_id {
+                        bot.edit_message_text(
+                            chat_id,
+                            msg_id,
+                            "🔍 <b>Node Finder</b>\n\nSelect an option:",
+                        )
+                        .parse_mode(teloxide::types::ParseMode::Html)
+                        .reply_markup(keyboards::main_menu())
+                        .await?;
                     }
                 }
             }
@@ -202,6 +389,14 @@ pub async fn handle_callback(
                         .await?;
                     }
                 }
+                "watchlist" => {
+                    if let Some(msg_id) = message_id {
+                        bot.edit_message_text(chat_id, msg_id, format_watchlist(&config.watchlist))
+                            .parse_mode(teloxide::types::ParseMode::Html)
+                            .reply_markup(keyboards::watchlist(&config.watchlist))
+                            .await?;
+                    }
+                }
                 "protocol" => {
                     let new_protocol = match config.protocol {
                         Protocol::Http => Protocol::Ws,
@@ -232,14 +427,154 @@ pub async fn handle_callback(
                         .await?;
                     }
                 }
+                "client" => {
+                    if let Some(msg_id) = message_id {
+                        bot.edit_message_text(
+                            chat_id,
+                            msg_id,
+                            format!(
+                                "Current: {}\n\nOnly return nodes running this client:",
+                                config.client_filter.map(|f| f.display_name()).unwrap_or("Any client")
+                            ),
+                        )
+                        .reply_markup(keyboards::client_selection())
+                        .await?;
+                    }
+                }
+                "archive" => {
+                    state.update_session(user_id, |s| {
+                        s.awaiting_archive_block = true;
+                    }).await;
+
+                    if let Some(msg_id) = message_id {
+                        bot.edit_message_text(
+                            chat_id,
+                            msg_id,
+                            format!(
+                                "Current: state from block {}\n\n\
+                                 Enter the oldest block archive nodes must serve state for \
+                                 (0 for full archive):",
+                                config.archive_from_block
+                            ),
+                        )
+                        .await?;
+                    }
+                }
+                "network" => {
+                    state.config_manager.update_user_config(user_id, |c| {
+                        c.network_filter = c.network_filter.next();
+                    }).await;
+
+                    let updated_config = state.config_manager.get_user_config(user_id).await;
+                    if let Some(msg_id) = message_id {
+                        bot.edit_message_text(chat_id, msg_id, "⚙️ Configuration")
+                            .reply_markup(keyboards::config_menu(&updated_config))
+                            .await?;
+                    }
+                }
+                "providers" => {
+                    state.update_session(user_id, |s| {
+                        s.awaiting_providers = true;
+                    }).await;
+
+                    let current = match config.excluded_providers.as_slice() {
+                        [] => "none".to_string(),
+                        providers => providers.join(", "),
+                    };
+                    let known: Vec<&str> = HOSTING_PROVIDERS.iter().map(|(name, _)| *name).collect();
+                    if let Some(msg_id) = message_id {
+                        bot.edit_message_text(
+                            chat_id,
+                            msg_id,
+                            format!(
+                                "Excluded providers: {}\n\n\
+                                 Enter providers to skip before validation, separated by commas: \
+                                 short names ({}), ASNs such as AS16509, or text from the \
+                                 org name. Send \"none\" to exclude nothing.\n\n\
+                                 Only Shodan candidates carry provider details; others are kept.",
+                                current,
+                                known.join(", ")
+                            ),
+                        )
+                        .await?;
+                    }
+                }
+                "subs" => {
+                    state.config_manager.update_user_config(user_id, |c| {
+                        c.check_subscriptions = !c.check_subscriptions;
+                    }).await;
+
+                    let updated_config = state.config_manager.get_user_config(user_id).await;
+                    if let Some(msg_id) = message_id {
+                        bot.edit_message_text(chat_id, msg_id, "⚙️ Configuration")
+                            .reply_markup(keyboards::config_menu(&updated_config))
+                            .await?;
+                    }
+                }
+                "tls" => {
+                    state.config_manager.update_user_config(user_id, |c| {
+                        c.valid_tls_only = !c.valid_tls_only;
+                    }).await;
+
+                    let updated_config = state.config_manager.get_user_config(user_id).await;
+                    if let Some(msg_id) = message_id {
+                        bot.edit_message_text(chat_id, msg_id, "⚙️ Configuration")
+                            .reply_markup(keyboards::config_menu(&updated_config))
+                            .await?;
+                    }
+                }
+                "exposed" => {
+                    state.config_manager.update_user_config(user_id, |c| {
+                        c.exclude_exposed = !c.exclude_exposed;
+                    }).await;
+
+                    let updated_config = state.config_manager.get_user_config(user_id).await;
+                    if let Some(msg_id) = message_id {
+                        bot.edit_message_text(chat_id, msg_id, "⚙️ Configuration")
+                            .reply_markup(keyboards::config_menu(&updated_config))
+                            .await?;
+                    }
+                }
+                "pages" => {
+                    if let Some(msg_id) = message_id {
+                        bot.edit_message_text(
+                            chat_id,
+                            msg_id,
+                            format!(
+                                "Current: {} pages per source per search\n\n\
+                                 Each Shodan result page holds up to 100 candidates \
+                                 and costs one query credit; other sources page and \
+                                 bill similarly.\n\n\
+                                 Select new page budget:",
+                                config.shodan_max_pages
+                            ),
+                        )
+                        .reply_markup(keyboards::shodan_pages_selection())
+                        .await?;
+                    }
+                }
+                "shodan" => {
+                    let mut text = String::from("🔎 Shodan filters:\n\n");
+                    for chain in get_chains() {
+                        let query = config.get_shodan_query(chain.id);
+                        let filters = if query.is_empty() { "default".to_string() } else { query.to_string() };
+                        text.push_str(&format!("{} {}: {}\n", chain.symbol, chain.name, filters));
+                    }
+                    text.push_str("\nSelect a chain to edit:");
+
+                    if let Some(msg_id) = message_id {
+                        bot.edit_message_text(chat_id, msg_id, text)
+                            .reply_markup(keyboards::shodan_query_selection())
+                            .await?;
+                    }
+                }
                 "rpcs" => {
                     let mut rpc_text = String::from("📡 Reference RPCs:\n\n");
-                    for chain in get_default_chains() {
-                        let rpc = config
-                            .reference_rpcs
-                            .get(&chain.id)
-                            .map(|s| s.as_str())
-                            .unwrap_or(&chain.default_rpc);
+                    for chain in get_chains() {
+                        let rpc = match config.get_reference_rpcs(chain.id) {
+                            [] => chain.reference_rpcs().join(", "),
+                            rpcs => rpcs.join(", "),
+                        };
                         rpc_text.push_str(&format!("{} {}: {}\n", chain.symbol, chain.name, rpc));
                     }
                     rpc_text.push_str("\nSelect a chain to edit:");
@@ -286,12 +621,79 @@ pub async fn handle_callback(
             }
         }
 
+        // Set client filter
+        ["setclient", code] => {
+            let filter = ClientFamily::from_code(code);
+            if filter.is_some() || *code == "any" {
+                state.config_manager.update_user_config(user_id, |c| {
+                    c.client_filter = filter;
+                }).await;
+
+                let config = state.config_manager.get_user_config(user_id).await;
+                if let Some(msg_id) = message_id {
+                    bot.edit_message_text(chat_id, msg_id, "⚙️ Configuration")
+                        .reply_markup(keyboards::config_menu(&config))
+                        .await?;
+                }
+            }
+        }
+
+        // Set Shodan page budget
+        ["setpages", pages] => {
+            if let Ok(n) = pages.parse::<u32>() {
+                state.config_manager.update_user_config(user_id, |c| {
+                    c.shodan_max_pages = n.max(1);
+                }).await;
+
+                let config = state.config_manager.get_user_config(user_id).await;
+                if let Some(msg_id) = message_id {
+                    bot.edit_message_text(chat_id, msg_id, "⚙️ Configuration")
+                        .reply_markup(keyboards::config_menu(&config))
+                        .await?;
+                }
+            }
+        }
+
+        // Edit Shodan filters - wait for the query text
+        ["shodanq", chain_id] => {
+            if let Ok(id) = chain_id.parse::<u64>() {
+                state.update_session(user_id, |s| {
+                    s.custom_chain_id = Some(id);
+                    s.awaiting_shodan_query = true;
+                }).await;
+
+                let chain_name = get_chain_by_id(id)
+                    .map(|c| c.name)
+                    .unwrap_or_else(|| format!("Chain {}", id));
+
+                if let Some(msg_id) = message_id {
+                    bot.edit_message_text(
+                        chat_id,
+                        msg_id,
+                        format!(
+                            "Enter Shodan filters for {}.\n\n\
+                             Ports are added to 8545, 8546 and 443; products and quoted banner text \
+                             also match nodes without the chain ID in their banner; ASNs and orgs \
+                             limit the search to those networks:\n\
+                             port:8547,9650 product:Nethermind asn:AS24940 org:\"Hetzner Online GmbH\" \"jsonrpc\"\n\n\
+                             Or replace the whole query ({{chain_id}} and {{chain_id_hex}} are filled in):\n\
+                             raw port:8545 \"Chain Id: {{chain_id_hex}}\"\n\n\
+                             Send \"default\" to go back to the default query.",
+                            chain_name
+                        ),
+                    )
+                    .await?;
+                }
+            }
+        }
+
         // Edit RPC - start wizard
         ["editrpc", chain_id] => {
             if let Ok(id) = chain_id.parse::<u64>() {
                 state.update_session(user_id, |s| {
                     s.custom_chain_id = Some(id);
                     s.awaiting_rpc_url = true;
+                    s.editing_rpc = true;
                 }).await;
 
                 let chain_name = get_chain_by_id(id)
@@ -302,7 +704,12 @@ pub async fn handle_callback(
                     bot.edit_message_text(
                         chat_id,
                         msg_id,
-                        format!("Enter new reference RPC URL for {}:", chain_name),
+                        format!(
+                            "Enter new reference RPC URL(s) for {}.\n\n\
+                             Several RPCs, separated by spaces or commas, are queried together \
+                             and their median height is used:",
+                            chain_name
+                        ),
                     )
                     .await?;
                 }
@@ -315,129 +722,100 @@ pub async fn handle_callback(
     Ok(())
 }
 
-async fn perform_search(
-    state: BotState,
+/// Nodes found healthy within this many seconds are shown before a new search
+const KNOWN_NODE_MAX_AGE: u64 = 6 * 3600;
+
+async fn send_known_nodes(
+    bot: &Bot,
+    chat_id: ChatId,
+    state: &BotState,
     user_id: i64,
     node_type: NodeType,
-    chain: Chain,
+    chain: &Chain,
     country_code: Option<&str>,
-) -> Result<Vec<ValidatedNode>, String> {
-    let config = state.config_manager.get_user_config(user_id).await;
-
-    // Get reference block number
-    let reference_rpc = config
-        .get_reference_rpc(chain.id)
-        .cloned()
-        .unwrap_or(chain.default_rpc.clone());
-
-    let reference_block = state
-        .http_validator
-        .get_current_block(&reference_rpc)
-        .await
-        .map_err(|e| format!("Reference node unavailable: {}. Configure a custom RPC in settings or try again later.", e))?;
-
-    // Query Shodan
-    let shodan_results = state
-        .shodan
-        .search_nodes(chain.id, country_code)
-        .await?;
+) -> ResponseResult<()> {
+    let Some(store) = &state.pipeline.store else {
+        return Ok(());
+    };
 
-    if shodan_results.is_empty() {
-        return Ok(vec![]);
+    let request = SearchRequest {
+        chain: chain.clone(),
+        node_type,
+        country_code: country_code.map(String::from),
+        config: state.config_manager.get_user_config(user_id).await,
+    };
+    let records = store.known_good(&request, KNOWN_NODE_MAX_AGE).await;
+    if records.is_empty() {
+        return Ok(());
     }
 
-    // Filter by protocol preference
-    // For WebSocket, we use port 8545 results and convert to 8546 (Shodan doesn't index 8546)
-    let filtered: Vec<ShodanResult> = shodan_results
-        .into_iter()
-        .filter(|r| r.is_http_port())
-        .collect();
-
-    // Determine how many nodes to validate
-    let target_count = match node_type {
-        NodeType::Full | NodeType::Archive => config.default_count as usize,
-        NodeType::Bulk => 50,
-    };
+    let now = store::now();
+    let mut msg = format!("⚡ Known good <b>{}</b> nodes from earlier searches:\n\n", chain.name);
+    for (i, record) in records.iter().enumerate() {
+        msg.push_str(&format!(
+            "{}. <code>{}</code> (checked {} ago, alive {})\n",
+            i + 1,
+            html_escape(&record.node.url),
+            format_duration(now.saturating_sub(record.last_checked)),
+            format_duration(record.lifetime())
+        ));
+    }
+    msg.push_str("\nRe-validating with a fresh search...");
 
-    // Validate nodes in parallel
-    let validation_futures: Vec<_> = filtered
-        .into_iter()
-        .take(target_count * 3) // Validate more to account for failures
-        .map(|result| {
-            let state = state.clone();
-            let chain = chain.clone();
-            let genesis_hash = chain.genesis_hash.clone();
-            async move {
-                let url = match config.protocol {
-                    Protocol::Http => result.http_url(),
-                    Protocol::Ws => result.ws_url(),
-                };
+    bot.send_message(chat_id, msg)
+        .parse_mode(teloxide::types::ParseMode::Html)
+        .await?;
+    Ok(())
+}
 
-                let validation_result = match config.protocol {
-                    Protocol::Http => {
-                        state
-                            .http_validator
-                            .validate(
-                                &url,
-                                chain.id,
-                                &genesis_hash,
-                                reference_block,
-                                config.sync_tolerance,
-                            )
-                            .await
-                    }
-                    Protocol::Ws => {
-                        state
-                            .ws_validator
-                            .validate(
-                                &url,
-                                chain.id,
-                                &genesis_hash,
-                                reference_block,
-                                config.sync_tolerance,
-                            )
-                            .await
-                    }
-                };
+fn format_tls(tls: &TlsInfo) -> String {
+    match (tls.valid, tls.expires_in(store::now())) {
+        (false, _) => "⚠️ invalid cert".to_string(),
+        (true, Some(0)) => "⚠️ cert expired".to_string(),
+        (true, Some(secs)) => format!("🔒 cert {}", format_duration(secs)),
+        (true, None) => "🔒 TLS".to_string(),
+    }
+}
 
-                validation_result.ok()
-            }
-        })
-        .collect();
-
-    let results: Vec<ValidatedNode> = join_all(validation_futures)
-        .await
-        .into_iter()
-        .flatten()
-        .collect();
-
-    // For archive nodes, additionally check archive capability
-    let mut final_results = if node_type == NodeType::Archive {
-        let archive_futures: Vec<_> = results
-            .into_iter()
-            .map(|node| {
-                let validator = state.archive_validator.clone();
-                async move { validator.validate_archive(node).await.ok() }
-            })
-            .collect();
+pub fn format_duration(secs: u64) -> String {
+    match secs {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m", s / 60),
+        s if s < 86_400 => format!("{}h", s / 3600),
+        s => format!("{}d", s / 86_400),
+    }
+}
 
-        join_all(archive_futures)
-            .await
-            .into_iter()
-            .flatten()
-            .filter(|n| n.is_archive)
-            .collect()
-    } else {
-        results
+/// Search the discovery sources, or validate every candidate of an imported list
+async fn perform_search(
+    state: BotState,
+    user_id: i64,
+    node_type: NodeType,
+    chain: Chain,
+    country_code: Option<&str>,
+    imported: Option<Arc<ImportedList>>,
+) -> Result<SearchOutcome, Error> {
+    let mut request = SearchRequest {
+        chain,
+        node_type,
+        country_code: country_code.map(String::from),
+        config: state.config_manager.get_user_config(user_id).await,
     };
 
-    // Sort by latency
-    final_results.sort_by_key(|n| n.latency_ms);
-
-    // Limit results
-    final_results.truncate(target_count);
+    let Some(list) = imported else {
+        return state.pipeline.search(&request).await;
+    };
 
-    Ok(final_results)
+    // Bulk only changes how results are shown, so validate the whole list either way
+    if request.node_type == NodeType::Bulk {
+        request.node_type = NodeType::Full;
+    }
+    request.config.default_count = list.len() as u32;
+    let pipeline = state
+        .pipeline
+        .clone()
+        .with_discovery(Discovery::new().with_source(list.as_ref().clone()));
+    pipeline.search(&request).await
 }
 
 async fn send_results(
@@ -446,6 +824,7 @@ async fn send_results(
     nodes: &[ValidatedNode],
     node_type: NodeType,
     chain_name: &str,
+    report_id: u64,
 ) -> ResponseResult<()> {
     if node_type == NodeType::Bulk {
         // JSON format, split if needed
@@ -462,14 +841,19 @@ async fn send_results(
 
         for (i, chunk) in chunks.iter().enumerate() {
             let msg = if chunks.len() > 1 {
-                format!("<b>{}</b> - Bulk Export\n<pre>{}</pre>\nPart {}/{}", chain_name, chunk, i + 1, chunks.len())
+                format!("<b>{}</b> - Bulk Export\n<pre>{}</pre>\nPart {}/{}", chain_name, html_escape(chunk), i + 1, chunks.len())
             } else {
-                format!("<b>{}</b> - Bulk Export\n<pre>{}</pre>", chain_name, chunk)
+                format!("<b>{}</b> - Bulk Export\n<pre>{}</pre>", chain_name, html_escape(chunk))
             };
 
-            bot.send_message(chat_id, msg)
-                .parse_mode(teloxide::types::ParseMode::Html)
-                .await?;
+            let request = bot
+                .send_message(chat_id, msg)
+                .parse_mode(teloxide::types::ParseMode::Html);
+            if i + 1 == chunks.len() {
+                request.reply_markup(keyboards::search_details(report_id)).await?;
+            } else {
+                request.await?;
+            }
         }
     } else {
         // List format
@@ -477,17 +861,183 @@ async fn send_results(
             NodeType::Full => "synced",
             NodeType::Archive => "archive",
             NodeType::Bulk => "bulk",
+            NodeType::Trace => "trace/debug",
         };
 
         let mut msg = format!("✅ Found {} {} <b>{}</b> nodes:\n\n", nodes.len(), type_name, chain_name);
         for (i, node) in nodes.iter().enumerate() {
-            msg.push_str(&format!("{}. <code>{}</code>\n", i + 1, node.url));
+            let mut details: Vec<String> = Vec::new();
+            if let Some(security) = node.security.as_ref().filter(|s| s.is_exposed()) {
+                details.push(format!("⚠️ exposed: {}", security.flags().join("/")));
+            }
+            if let Some(client) = &node.client {
+                details.push(html_escape(&client.label()));
+            }
+            if let Some(label) = node.host.as_ref().and_then(|h| h.label()) {
+                details.push(format!("🏢 {}", html_escape(&label)));
+            }
+            if let Some(tls) = &node.tls {
+                details.push(format_tls(tls));
+            }
+            if let Some(delay) = node.header_delay_ms {
+                details.push(format!("newHeads +{}ms", delay));
+            }
+            match node.oldest_state_block {
+                Some(_) if node.is_archive => details.push("full archive".to_string()),
+                Some(block) => details.push(format!("state from #{}", block)),
+                None => {}
+            }
+            let namespaces = node.namespaces();
+            if !namespaces.is_empty() {
+                details.push(namespaces.join("/"));
+            }
+
+            if details.is_empty() {
+                msg.push_str(&format!("{}. <code>{}</code>\n", i + 1, html_escape(&node.url)));
+            } else {
+                msg.push_str(&format!(
+                    "{}. <code>{}</code> ({})\n",
+                    i + 1,
+                    html_escape(&node.url),
+                    details.join(", ")
+                ));
+            }
         }
 
         bot.send_message(chat_id, msg)
             .parse_mode(teloxide::types::ParseMode::Html)
+            .reply_markup(keyboards::search_results(report_id, nodes.len()))
             .await?;
     }
 
     Ok(())
 }
+
+fn format_watchlist(watchlist: &[WatchedNode]) -> String {
+    if watchlist.is_empty() {
+        return "👁 <b>Watchlist</b>\n\nNo nodes pinned yet. Use the 📌 buttons under search results.".to_string();
+    }
+
+    let now = store::now();
+    let mut msg = String::from("👁 <b>Watchlist</b>\n\n");
+    for (i, node) in watchlist.iter().enumerate() {
+        let chain_name = get_chain_by_id(node.chain_id)
+            .map(|c| c.name)
+            .unwrap_or_else(|| format!("Chain {}", node.chain_id));
+        msg.push_str(&format!(
+            "{}. <code>{}</code> ({}, {} for {})\n",
+            i + 1,
+            html_escape(&node.url),
+            chain_name,
+            node.status.label(),
+            format_duration(now.saturating_sub(node.status_since))
+        ));
+    }
+    msg
+}
+
+/// Render the validation funnel: how many candidates each stage dropped and why
+fn format_diagnostics(d: &SearchDiagnostics) -> String {
+    let mut msg = String::from("📊 <b>Search details</b>\n\n");
+    match (d.reference_block, d.reference_fallback) {
+        (Some(block), false) => msg.push_str(&format!(
+            "Reference: block {} (median of {} RPC(s))\n",
+            block, d.references_responded
+        )),
+        (Some(block), true) => msg.push_str(&format!(
+            "⚠️ Reference RPCs unreachable, synced against the median candidate: block {}\n",
+            block
+        )),
+        (None, _) => msg.push_str("⚠️ Reference RPCs unreachable\n"),
+    }
+    if let Some(block) = d.checkpoint_disputed {
+        msg.push_str(&format!(
+            "⚠️ Reference RPCs disagree on block {} with no majority, fork check skipped\n",
+            block
+        ));
+    }
+    for (source, report) in &d.sources {
+        match &report.error {
+            Some(error) => msg.push_str(&format!("⚠️ {}: {}\n", source, html_escape(error))),
+            None => msg.push_str(&format!(
+                "{}: {} candidates ({} total matches, {} page(s))\n",
+                source, report.candidates, report.total, report.pages
+            )),
+        }
+    }
+    if d.duplicates > 0 {
+        msg.push_str(&format!(
+            "{} unique candidates ({} found by several sources)\n",
+            d.candidates_found, d.duplicates
+        ));
+    }
+
+    let stages = [
+        ("Not on an RPC port or TLS, or a URL of the other protocol", d.filtered_by_port),
+        ("Network filter or excluded provider", d.filtered_by_provider),
+        ("Not validated (over count × 3 budget)", d.over_budget),
+        ("Unreachable (timeout)", d.rejected("timeout")),
+        ("Unreachable (connection refused/reset)", d.rejected("connect")),
+        ("HTTP error status", d.rejected("http_status")),
+        ("WebSocket error", d.rejected("websocket")),
+        ("RPC error", d.rejected("rpc_error")),
+        ("Invalid response", d.rejected("invalid_response")),
+        ("Wrong chain ID", d.rejected("chain_id_mismatch")),
+        ("Genesis mismatch", d.rejected("genesis_mismatch")),
+        ("Forked/fake chain (block hash mismatch)", d.rejected("fork")),
+        ("Out of sync", d.rejected("not_synced")),
+        ("Client filtered", d.client_filtered),
+        ("No valid TLS certificate", d.no_valid_tls),
+        ("Unlocked accounts/admin", d.exposed_excluded),
+        ("No newHeads subscription", d.no_subscription),
+        ("No archive state", d.rejected("archive_missing") + d.not_archive),
+        ("No trace/debug methods", d.no_trace_support),
+    ];
+
+    for (label, count) in stages.iter().filter(|(_, count)| *count > 0) {
+        msg.push_str(&format!("❌ {}: {}\n", label, count));
+    }
+
+    if !d.ws_endpoints.is_empty() {
+        let endpoints: Vec<String> = d
+            .ws_endpoints
+            .iter()
+            .map(|(endpoint, count)| format!("{} ×{}", endpoint, count))
+            .collect();
+        msg.push_str(&format!("\n🔌 WS endpoints: {}\n", endpoints.join(", ")));
+    }
+
+    if !d.providers.is_empty() {
+        let mut providers: Vec<(&String, &usize)> = d.providers.iter().collect();
+        providers.sort_by_key(|(_, count)| std::cmp::Reverse(**count));
+        let providers: Vec<String> = providers
+            .into_iter()
+            .map(|(provider, count)| format!("{} ×{}", html_escape(provider), count))
+            .collect();
+        msg.push_str(&format!("\n🏢 Providers: {}\n", providers.join(", ")));
+    }
+
+    msg.push_str(&format!("\n✅ Passed: {}, returned: {}", d.passed, d.returned));
+    msg
+}
+
+/// Match counts per facet, most common values first
+fn format_facets(chain_name: &str, facets: &ShodanFacets) -> String {
+    let mut msg = format!("📊 <b>{}</b> on Shodan: {} matches\n", html_escape(chain_name), facets.total);
+    for (facet, values) in &facets.facets {
+        if values.is_empty() {
+            continue;
+        }
+        let values: Vec<String> = values
+            .iter()
+            .map(|(value, count)| format!("{} {}", html_escape(value), count))
+            .collect();
+        msg.push_str(&format!("\n<b>{}</b>: {}\n", facet, values.join(", ")));
+    }
+    msg.push_str("\nSelect a location:");
+    msg
+}
+
+pub fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
@@ -1,6 +1,14 @@
-use teloxide::{prelude::*, utils::command::BotCommands};
+use teloxide::{net::Download, prelude::*, utils::command::BotCommands};
+use super::callbacks::html_escape;
 use super::keyboards;
 use super::state::BotState;
+use node_finder::chains::{is_block_hash, Chain};
+use node_finder::discovery::import::{ImportedList, MAX_IMPORT_CANDIDATES};
+use node_finder::shodan::ShodanQuery;
+use std::sync::Arc;
+
+/// Largest file the Bot API lets bots download
+const MAX_IMPORT_FILE_BYTES: u32 = 20 * 1024 * 1024;
 
 #[derive(BotCommands, Clone)]
 #[command(rename_rule = "lowercase", description = "Available commands:")]
@@ -30,7 +38,7 @@ async fn handle_start(bot: Bot, msg: Message, state: BotState) -> ResponseResult
     state.clear_session(user_id).await;
 
     let text = "🔍 <b>Node Finder</b>\n\n\
-                Find public RPC nodes from Shodan.\n\n\
+                Find public RPC nodes from Shodan and other internet scanners.\n\n\
                 Select an option below:";
 
     bot.send_message(msg.chat.id, text)
@@ -49,12 +57,22 @@ async fn handle_help(bot: Bot, msg: Message) -> ResponseResult<()> {
                 <b>Node Types:</b>\n\
                 • Full Node - Synced nodes\n\
                 • Archive Node - Nodes with historical data\n\
-                • Bulk Nodes - JSON export of many nodes\n\n\
+                • Bulk Nodes - JSON export of many nodes\n\
+                • Trace/Debug Node - Nodes exposing debug_/trace_ methods\n\n\
+                <b>Import:</b>\n\
+                Send a file of candidates (ip:port lines, masscan JSON or list output, \
+                or RPC URLs) to validate it instead of searching.\n\n\
                 <b>Config:</b>\n\
                 • Set default node count\n\
                 • Choose HTTP or WS protocol\n\
                 • Adjust sync tolerance\n\
-                • Set custom reference RPCs";
+                • Set custom reference RPCs\n\
+                • Add Shodan filters per chain (ports, products, ASN/org)\n\
+                • Search residential or hosting networks only, or skip providers such as AWS\n\n\
+                <b>Watchlist:</b>\n\
+                Pin nodes with the 📌 buttons under results to get alerts when they go down, \
+                fall out of sync or change chain, plus a daily digest with replacements. \
+                Manage pinned nodes under Config → Watchlist.";
 
     bot.send_message(msg.chat.id, text)
         .parse_mode(teloxide::types::ParseMode::Html)
@@ -63,6 +81,61 @@ async fn handle_help(bot: Bot, msg: Message) -> ResponseResult<()> {
     Ok(())
 }
 
+/// Handle uploaded candidate lists: parse them and start the node type → chain flow
+pub async fn handle_document(bot: Bot, msg: Message, state: BotState) -> ResponseResult<()> {
+    let user_id = msg.from().map(|u| u.id.0 as i64).unwrap_or(0);
+    let Some(document) = msg.document() else {
+        return Ok(());
+    };
+
+    if document.file.size > MAX_IMPORT_FILE_BYTES {
+        bot.send_message(msg.chat.id, "❌ File too large. Bots can only download files up to 20 MB.")
+            .await?;
+        return Ok(());
+    }
+
+    let file = bot.get_file(&document.file.id).await?;
+    let mut contents = Vec::new();
+    if let Err(e) = bot.download_file(&file.path, &mut contents).await {
+        log::warn!("Failed to download import from user {}: {}", user_id, e);
+        bot.send_message(msg.chat.id, "❌ Could not download the file. Please try again.")
+            .await?;
+        return Ok(());
+    }
+
+    let list = ImportedList::parse(&String::from_utf8_lossy(&contents));
+    if list.is_empty() {
+        bot.send_message(
+            msg.chat.id,
+            "❌ No candidates found. Send ip:port lines, masscan JSON or list output, \
+             or http/https/ws/wss URLs.",
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let mut text = format!("📥 Imported {} candidates", list.len());
+    if list.skipped > 0 {
+        text.push_str(&format!(", skipped {} unreadable lines", list.skipped));
+    }
+    if list.dropped > 0 {
+        text.push_str(&format!(", dropped {} over the {} limit", list.dropped, MAX_IMPORT_CANDIDATES));
+    }
+    text.push_str(".\n\nSelect a node type to validate them as:");
+
+    // A fresh session, so an abandoned wizard does not swallow the next steps
+    state.clear_session(user_id).await;
+    state.update_session(user_id, |s| {
+        s.imported = Some(Arc::new(list));
+    }).await;
+
+    bot.send_message(msg.chat.id, text)
+        .reply_markup(keyboards::main_menu())
+        .await?;
+
+    Ok(())
+}
+
 /// Handle text messages (for custom chain wizard)
 pub async fn handle_message(bot: Bot, msg: Message, state: BotState) -> ResponseResult<()> {
     let user_id = msg.from().map(|u| u.id.0 as i64).unwrap_or(0);
@@ -73,6 +146,30 @@ pub async fn handle_message(bot: Bot, msg: Message, state: BotState) -> Response
         // Parse chain ID
         match text.trim().parse::<u64>() {
             Ok(chain_id) => {
+                // Registry chains and chains the user already added have reference RPCs
+                let config = state.config_manager.get_user_config(user_id).await;
+                if let Some(chain) = config.get_chain(chain_id) {
+                    state.update_session(user_id, |s| {
+                        s.awaiting_chain_id = false;
+                    }).await;
+
+                    if !chain.genesis_hash.is_empty() {
+                        let text = format!("✅ {} selected.\n\nSelect a location:", chain.name);
+                        state.update_session(user_id, |s| {
+                            s.chain = Some(chain);
+                        }).await;
+                        bot.send_message(msg.chat.id, text)
+                            .reply_markup(keyboards::location_selection(
+                                session.imported.is_some(),
+                                state.shodan.is_some(),
+                            ))
+                            .await?;
+                    } else {
+                        confirm_new_genesis(&bot, msg.chat.id, &state, user_id, chain).await?;
+                    }
+                    return Ok(());
+                }
+
                 state.update_session(user_id, |s| {
                     s.custom_chain_id = Some(chain_id);
                     s.awaiting_chain_id = false;
@@ -94,38 +191,209 @@ pub async fn handle_message(bot: Bot, msg: Message, state: BotState) -> Response
                 .await?;
             }
         }
+    } else if session.awaiting_archive_block {
+        match text.trim().parse::<u64>() {
+            Ok(block) => {
+                state.config_manager.update_user_config(user_id, |config| {
+                    config.archive_from_block = block;
+                }).await;
+                state.update_session(user_id, |s| {
+                    s.awaiting_archive_block = false;
+                }).await;
+
+                let config = state.config_manager.get_user_config(user_id).await;
+                bot.send_message(msg.chat.id, "⚙️ Configuration")
+                    .reply_markup(keyboards::config_menu(&config))
+                    .await?;
+            }
+            Err(_) => {
+                bot.send_message(
+                    msg.chat.id,
+                    "❌ Invalid block number. Please enter a whole number (0 for full archive):",
+                )
+                .await?;
+            }
+        }
+    } else if session.awaiting_shodan_query {
+        let chain_id = session.custom_chain_id.unwrap_or(1);
+        let query = match text.trim() {
+            "default" => Ok(ShodanQuery::default()),
+            filters => ShodanQuery::parse(filters),
+        };
+
+        match query {
+            Ok(query) => {
+                state.config_manager.update_user_config(user_id, |config| {
+                    if query.is_empty() {
+                        config.shodan_queries.remove(&chain_id);
+                    } else {
+                        config.shodan_queries.insert(chain_id, query);
+                    }
+                }).await;
+                state.update_session(user_id, |s| {
+                    s.awaiting_shodan_query = false;
+                }).await;
+
+                let config = state.config_manager.get_user_config(user_id).await;
+                bot.send_message(msg.chat.id, "✅ Shodan filters updated.\n\n⚙️ Configuration")
+                    .reply_markup(keyboards::config_menu(&config))
+                    .await?;
+            }
+            Err(e) => {
+                bot.send_message(msg.chat.id, format!("❌ {}. Please try again:", e))
+                    .await?;
+            }
+        }
+    } else if session.awaiting_providers {
+        let providers: Vec<String> = match text.trim() {
+            "none" => Vec::new(),
+            list => list
+                .split(',')
+                .map(|p| p.trim().to_lowercase())
+                .filter(|p| !p.is_empty())
+                .collect(),
+        };
+
+        state.config_manager.update_user_config(user_id, |config| {
+            config.excluded_providers = providers;
+        }).await;
+        state.update_session(user_id, |s| {
+            s.awaiting_providers = false;
+        }).await;
+
+        let config = state.config_manager.get_user_config(user_id).await;
+        bot.send_message(msg.chat.id, "✅ Excluded providers updated.\n\n⚙️ Configuration")
+            .reply_markup(keyboards::config_menu(&config))
+            .await?;
     } else if session.awaiting_rpc_url {
-        // Parse and validate URL
-        if text.starts_with("http://") || text.starts_with("https://") {
-            let chain_id = session.custom_chain_id.unwrap_or(1);
+        // Parse and validate URLs; several can be given, separated by spaces or commas
+        let urls: Vec<String> = text
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|u| !u.is_empty())
+            .map(String::from)
+            .collect();
+        if urls.is_empty() || !urls.iter().all(|u| u.starts_with("http://") || u.starts_with("https://")) {
+            bot.send_message(
+                msg.chat.id,
+                "❌ Invalid URL. Please enter a valid RPC URL starting with http:// or https://:",
+            )
+            .await?;
+            return Ok(());
+        }
 
-            // Store custom RPC in user config
+        let chain_id = session.custom_chain_id.unwrap_or(1);
+
+        if session.editing_rpc {
             state.config_manager.update_user_config(user_id, |config| {
-                config.reference_rpcs.insert(chain_id, text.to_string());
+                config.reference_rpcs.insert(chain_id, urls);
             }).await;
-
             state.update_session(user_id, |s| {
                 s.awaiting_rpc_url = false;
-                s.chain = Some(crate::chains::Chain {
-                    id: chain_id,
-                    name: format!("Chain {}", chain_id),
-                    symbol: "🔧".to_string(),
-                    default_rpc: text.to_string(),
-                    genesis_hash: String::new(), // Custom chains skip genesis check
-                });
+                s.editing_rpc = false;
             }).await;
 
-            bot.send_message(msg.chat.id, "✅ Custom chain configured!\n\nSelect a location:")
-                .reply_markup(keyboards::location_selection())
+            let config = state.config_manager.get_user_config(user_id).await;
+            bot.send_message(msg.chat.id, "✅ Reference RPC updated.\n\n⚙️ Configuration")
+                .reply_markup(keyboards::config_menu(&config))
                 .await?;
+            return Ok(());
+        }
+
+        let chain = Chain {
+            id: chain_id,
+            name: format!("Chain {}", chain_id),
+            symbol: "🔧".to_string(),
+            default_rpc: urls[0].clone(),
+            fallback_rpcs: urls[1..].to_vec(),
+            genesis_hash: String::new(),
+            archive_probe_heights: vec![1],
+            first_state_block: 0,
+            block_time_ms: 12_000,
+        };
+
+        if confirm_new_genesis(&bot, msg.chat.id, &state, user_id, chain).await? {
+            state.update_session(user_id, |s| {
+                s.awaiting_rpc_url = false;
+            }).await;
         } else {
+            bot.send_message(msg.chat.id, "Enter another reference RPC URL:")
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Fetch block 0 from the chain's default RPC (for a new custom chain, the
+/// URL the user just typed) and ask the user to confirm it, giving the chain
+/// the same honeypot protection as built-in chains.
+/// Returns false (after telling the user why) if the RPC is unusable.
+pub async fn confirm_new_genesis(
+    bot: &Bot,
+    chat_id: ChatId,
+    state: &BotState,
+    user_id: i64,
+    mut chain: Chain,
+) -> ResponseResult<bool> {
+    let config = state.config_manager.get_user_config(user_id).await;
+    let url = chain.default_rpc.clone();
+
+    let rpc_chain_id = state.pipeline.http_validator.get_chain_id(&url).await;
+    let genesis = state.pipeline.http_validator.get_genesis_hash(&url).await;
+
+    chain.genesis_hash = match (rpc_chain_id, genesis) {
+        (Ok(id), Ok(hash)) if id == chain.id && is_block_hash(&hash) => hash,
+        (Ok(id), Ok(hash)) if id == chain.id => {
             bot.send_message(
-                msg.chat.id,
-                "❌ Invalid URL. Please enter a valid RPC URL starting with http:// or https://:",
+                chat_id,
+                format!("❌ {} returned a malformed genesis hash {:?}.", url, hash),
             )
             .await?;
+            return Ok(false);
         }
-    }
+        (Ok(id), Ok(_)) => {
+            bot.send_message(
+                chat_id,
+                format!("❌ {} serves chain {} instead of {}.", url, id, chain.id),
+            )
+            .await?;
+            return Ok(false);
+        }
+        (Err(e), _) | (_, Err(e)) => {
+            bot.send_message(
+                chat_id,
+                format!("❌ Could not fetch genesis block from {} ({}).", url, e),
+            )
+            .await?;
+            return Ok(false);
+        }
+    };
 
-    Ok(())
+    let warning = match config.custom_chains.get(&chain.id) {
+        Some(pinned) if !pinned.genesis_hash.is_empty() && pinned.genesis_hash != chain.genesis_hash => {
+            format!(
+                "\n\n⚠️ This differs from the genesis you pinned earlier:\n<code>{}</code>\n\
+                 The reference RPC may be on another network or spoofed.",
+                html_escape(&pinned.genesis_hash)
+            )
+        }
+        _ => String::new(),
+    };
+
+    let text = format!(
+        "Genesis block hash for {} (chain {}):\n<code>{}</code>{}\n\n\
+         Confirm it matches the chain you expect. Nodes with a different genesis will be rejected.",
+        html_escape(&chain.name), chain.id, chain.genesis_hash, warning
+    );
+
+    state.update_session(user_id, |s| {
+        s.pending_chain = Some(chain);
+    }).await;
+
+    bot.send_message(chat_id, text)
+        .parse_mode(teloxide::types::ParseMode::Html)
+        .reply_markup(keyboards::genesis_confirmation())
+        .await?;
+
+    Ok(true)
 }
@@ -1,20 +1,22 @@
 use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
-use crate::chains::get_default_chains;
+use node_finder::chains::get_chains;
 use crate::bot::state::LOCATIONS;
-use crate::config::{Protocol, UserConfig};
+use node_finder::config::{Protocol, UserConfig, WatchedNode};
+use node_finder::validator::client::ClientFamily;
 
 pub fn main_menu() -> InlineKeyboardMarkup {
     let buttons = vec![
         vec![InlineKeyboardButton::callback("🔄 Full Node", "node:full")],
         vec![InlineKeyboardButton::callback("📚 Archive Node", "node:archive")],
         vec![InlineKeyboardButton::callback("📦 Bulk Nodes", "node:bulk")],
+        vec![InlineKeyboardButton::callback("🔬 Trace/Debug Node", "node:trace")],
         vec![InlineKeyboardButton::callback("⚙️ Config", "config:menu")],
     ];
     InlineKeyboardMarkup::new(buttons)
 }
 
 pub fn chain_selection() -> InlineKeyboardMarkup {
-    let mut buttons: Vec<Vec<InlineKeyboardButton>> = get_default_chains()
+    let mut buttons: Vec<Vec<InlineKeyboardButton>> = get_chains()
         .into_iter()
         .map(|c| {
             vec![InlineKeyboardButton::callback(
@@ -30,7 +32,17 @@ pub fn chain_selection() -> InlineKeyboardMarkup {
     InlineKeyboardMarkup::new(buttons)
 }
 
-pub fn location_selection() -> InlineKeyboardMarkup {
+/// Where to search; with an imported list there is nothing to search, only
+/// the list to validate. With Shodan configured, match counts per country and
+/// provider can be looked up first.
+pub fn location_selection(imported: bool, facets: bool) -> InlineKeyboardMarkup {
+    if imported {
+        return InlineKeyboardMarkup::new(vec![
+            vec![InlineKeyboardButton::callback("📥 Validate imported list", "location:import")],
+            vec![InlineKeyboardButton::callback("« Back", "back:chain")],
+        ]);
+    }
+
     let mut buttons: Vec<Vec<InlineKeyboardButton>> = LOCATIONS
         .iter()
         .map(|loc| {
@@ -42,6 +54,9 @@ pub fn location_selection() -> InlineKeyboardMarkup {
         .collect();
 
     buttons.push(vec![InlineKeyboardButton::callback("🌍 All Locations", "location:all")]);
+    if facets {
+        buttons.push(vec![InlineKeyboardButton::callback("📊 Shodan counts", "facets:show")]);
+    }
     buttons.push(vec![InlineKeyboardButton::callback("« Back", "back:chain")]);
 
     InlineKeyboardMarkup::new(buttons)
@@ -66,7 +81,62 @@ pub fn config_menu(config: &UserConfig) -> InlineKeyboardMarkup {
             format!("🔄 Sync tolerance: {} blocks", config.sync_tolerance),
             "config:sync",
         )],
+        vec![InlineKeyboardButton::callback(
+            match config.archive_from_block {
+                0 => "📚 Archive state from: genesis".to_string(),
+                block => format!("📚 Archive state from: block {}", block),
+            },
+            "config:archive",
+        )],
+        vec![InlineKeyboardButton::callback(
+            format!(
+                "🧬 Client: {}",
+                config.client_filter.map(|f| f.display_name()).unwrap_or("Any")
+            ),
+            "config:client",
+        )],
+        vec![InlineKeyboardButton::callback(
+            format!("🏢 Networks: {}", config.network_filter.display_name()),
+            "config:network",
+        )],
+        vec![InlineKeyboardButton::callback(
+            format!("🚫 Excluded providers: {}", config.excluded_providers.len()),
+            "config:providers",
+        )],
+        vec![InlineKeyboardButton::callback(
+            format!(
+                "🛡️ Exposed nodes: {}",
+                if config.exclude_exposed { "Exclude" } else { "Include" }
+            ),
+            "config:exposed",
+        )],
+        vec![InlineKeyboardButton::callback(
+            format!(
+                "🔒 Valid TLS only: {}",
+                if config.valid_tls_only { "On" } else { "Off" }
+            ),
+            "config:tls",
+        )],
+        vec![InlineKeyboardButton::callback(
+            format!(
+                "📡 WS newHeads check: {}",
+                if config.check_subscriptions { "On" } else { "Off" }
+            ),
+            "config:subs",
+        )],
+        vec![InlineKeyboardButton::callback(
+            format!("💳 Result pages: {}", config.shodan_max_pages),
+            "config:pages",
+        )],
         vec![InlineKeyboardButton::callback("📡 Reference RPCs", "config:rpcs")],
+        vec![InlineKeyboardButton::callback(
+            format!("🔎 Shodan filters: {} chains", config.shodan_queries.len()),
+            "config:shodan",
+        )],
+        vec![InlineKeyboardButton::callback(
+            format!("👁 Watchlist: {} nodes", config.watchlist.len()),
+            "config:watchlist",
+        )],
         vec![InlineKeyboardButton::callback("« Back", "back:main")],
     ];
 
@@ -114,8 +184,45 @@ pub fn sync_tolerance_selection() -> InlineKeyboardMarkup {
     InlineKeyboardMarkup::new(buttons)
 }
 
+pub fn shodan_pages_selection() -> InlineKeyboardMarkup {
+    let pages = [1, 2, 3, 5, 10];
+    let buttons: Vec<Vec<InlineKeyboardButton>> = pages
+        .chunks(3)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .map(|&n| InlineKeyboardButton::callback(n.to_string(), format!("setpages:{}", n)))
+                .collect()
+        })
+        .collect();
+
+    let mut buttons = buttons;
+    buttons.push(vec![InlineKeyboardButton::callback("« Back", "config:menu")]);
+
+    InlineKeyboardMarkup::new(buttons)
+}
+
+pub fn client_selection() -> InlineKeyboardMarkup {
+    let mut buttons: Vec<Vec<InlineKeyboardButton>> = ClientFamily::KNOWN
+        .chunks(3)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .map(|f| {
+                    InlineKeyboardButton::callback(f.display_name(), format!("setclient:{}", f.code()))
+                })
+                .collect()
+        })
+        .collect();
+
+    buttons.push(vec![InlineKeyboardButton::callback("Any client", "setclient:any")]);
+    buttons.push(vec![InlineKeyboardButton::callback("« Back", "config:menu")]);
+
+    InlineKeyboardMarkup::new(buttons)
+}
+
 pub fn rpc_selection() -> InlineKeyboardMarkup {
-    let chains = get_default_chains();
+    let chains = get_chains();
     let buttons: Vec<Vec<InlineKeyboardButton>> = chains
         .into_iter()
         .map(|c| {
@@ -132,8 +239,55 @@ pub fn rpc_selection() -> InlineKeyboardMarkup {
     InlineKeyboardMarkup::new(buttons)
 }
 
-pub fn back_to_config() -> InlineKeyboardMarkup {
+pub fn shodan_query_selection() -> InlineKeyboardMarkup {
+    let mut buttons: Vec<Vec<InlineKeyboardButton>> = get_chains()
+        .into_iter()
+        .map(|c| {
+            vec![InlineKeyboardButton::callback(
+                format!("{} {}", c.symbol, c.name),
+                format!("shodanq:{}", c.id),
+            )]
+        })
+        .collect();
+
+    buttons.push(vec![InlineKeyboardButton::callback("« Back", "config:menu")]);
+
+    InlineKeyboardMarkup::new(buttons)
+}
+
+pub fn genesis_confirmation() -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![
+        vec![InlineKeyboardButton::callback("✅ Confirm genesis", "genesis:confirm")],
+        vec![InlineKeyboardButton::callback("❌ Cancel", "genesis:cancel")],
+    ])
+}
+
+/// Pin buttons for each listed node, then the Details button
+pub fn search_results(report_id: u64, node_count: usize) -> InlineKeyboardMarkup {
+    let pins: Vec<InlineKeyboardButton> = (0..node_count)
+        .map(|i| InlineKeyboardButton::callback(format!("📌 {}", i + 1), format!("watch:{}:{}", report_id, i)))
+        .collect();
+
+    let mut buttons: Vec<Vec<InlineKeyboardButton>> = pins.chunks(5).map(|row| row.to_vec()).collect();
+    buttons.push(vec![InlineKeyboardButton::callback("📊 Details", format!("details:{}", report_id))]);
+
+    InlineKeyboardMarkup::new(buttons)
+}
+
+pub fn watchlist(watchlist: &[WatchedNode]) -> InlineKeyboardMarkup {
+    let mut buttons: Vec<Vec<InlineKeyboardButton>> = watchlist
+        .iter()
+        .enumerate()
+        .map(|(i, node)| vec![InlineKeyboardButton::callback(format!("🗑 Unwatch #{}", i + 1), format!("unwatch:{}", node.key()))])
+        .collect();
+
+    buttons.push(vec![InlineKeyboardButton::callback("« Back", "config:menu")]);
+
+    InlineKeyboardMarkup::new(buttons)
+}
+
+pub fn search_details(report_id: u64) -> InlineKeyboardMarkup {
     InlineKeyboardMarkup::new(vec![
-        vec![InlineKeyboardButton::callback("« Back", "config:menu")],
+        vec![InlineKeyboardButton::callback("📊 Details", format!("details:{}", report_id))],
     ])
 }
@@ -1,6 +1,7 @@
 pub mod keyboards;
 pub mod commands;
 pub mod callbacks;
+pub mod monitor;
 pub mod state;
 
 pub use state::BotState;
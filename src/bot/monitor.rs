@@ -0,0 +1,236 @@
+use super::callbacks::{format_duration, html_escape};
+use super::state::BotState;
+use futures::stream::{self, StreamExt};
+use node_finder::config::{UserConfig, WatchStatus, WatchedNode};
+use node_finder::error::Error;
+use node_finder::search::{NodeType, SearchRequest};
+use node_finder::store;
+use node_finder::validator::ValidatedNode;
+use std::collections::HashMap;
+use std::env;
+use std::time::Duration;
+use teloxide::prelude::*;
+
+const DEFAULT_WATCH_INTERVAL_SECS: u64 = 300;
+const WATCH_CONCURRENCY: usize = 20;
+const DIGEST_INTERVAL_SECS: u64 = 86_400;
+/// Replacement nodes suggested per chain in the daily digest
+const DIGEST_REPLACEMENTS: usize = 3;
+
+/// Check every user's watched nodes on an interval (`WATCH_INTERVAL_SECS`),
+/// alert on status changes and send a daily digest
+pub async fn run(bot: Bot, state: BotState) {
+    let interval = env::var("WATCH_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_WATCH_INTERVAL_SECS);
+    if interval == 0 {
+        log::info!("Watchlist monitoring disabled");
+        return;
+    }
+
+    let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+    loop {
+        ticker.tick().await;
+        check_watchlists(&bot, &state).await;
+    }
+}
+
+async fn check_watchlists(bot: &Bot, state: &BotState) {
+    let users: Vec<(i64, UserConfig)> = state
+        .config_manager
+        .all_user_configs()
+        .await
+        .into_iter()
+        .filter(|(_, config)| !config.watchlist.is_empty())
+        .collect();
+    if users.is_empty() {
+        return;
+    }
+
+    // Validate each watched node once, however many users watch it. Sync is
+    // judged per user afterwards, so validation itself accepts any height.
+    let mut targets: HashMap<(u64, String), UserConfig> = HashMap::new();
+    for (_, config) in &users {
+        for watched in &config.watchlist {
+            targets
+                .entry((watched.chain_id, watched.url.clone()))
+                .or_insert_with(|| config.clone());
+        }
+    }
+
+    let results: HashMap<(u64, String), Result<ValidatedNode, Error>> = stream::iter(targets)
+        .map(|((chain_id, url), config)| async move {
+            let result = match config.get_chain(chain_id) {
+                Some(chain) if !chain.genesis_hash.is_empty() => {
+                    if url.starts_with("ws://") || url.starts_with("wss://") {
+                        state.pipeline.ws_validator.validate(&url, chain_id, &chain.genesis_hash, 0, u64::MAX, None).await
+                    } else {
+                        state.pipeline.http_validator.validate(&url, chain_id, &chain.genesis_hash, 0, u64::MAX, None).await
+                    }
+                }
+                _ => Err(Error::InvalidResponse(format!("chain {} has no genesis hash", chain_id))),
+            };
+            ((chain_id, url), result)
+        })
+        .buffer_unordered(WATCH_CONCURRENCY)
+        .collect()
+        .await;
+
+    // Reference heights per chain and reference RPC override
+    let mut references: HashMap<(u64, Vec<String>), Option<u64>> = HashMap::new();
+
+    for (user_id, config) in users {
+        let mut statuses = Vec::new();
+        for watched in &config.watchlist {
+            let Some(result) = results.get(&(watched.chain_id, watched.url.clone())) else {
+                continue;
+            };
+
+            let reference_key = (watched.chain_id, config.get_reference_rpcs(watched.chain_id).to_vec());
+            if !references.contains_key(&reference_key) {
+                let reference = match config.get_chain(watched.chain_id) {
+                    Some(chain) => state.pipeline.reference_block(&chain, &config).await.ok(),
+                    None => None,
+                };
+                references.insert(reference_key.clone(), reference);
+            }
+
+            let status = classify(result, references[&reference_key], config.sync_tolerance);
+            statuses.push((watched.clone(), status));
+        }
+
+        let now = store::now();
+        for (watched, status) in &statuses {
+            if *status != watched.status {
+                send_alert(bot, user_id, watched, *status).await;
+            }
+        }
+
+        let send_digest = now.saturating_sub(config.last_digest) >= DIGEST_INTERVAL_SECS;
+        state
+            .config_manager
+            .update_user_config(user_id, |c| {
+                for (watched, status) in &statuses {
+                    let Some(entry) = c
+                        .watchlist
+                        .iter_mut()
+                        .find(|w| w.chain_id == watched.chain_id && w.url == watched.url)
+                    else {
+                        continue;
+                    };
+                    if entry.status != *status {
+                        entry.status = *status;
+                        entry.status_since = now;
+                    }
+                    entry.last_checked = now;
+                }
+                // The first digest goes out a day after monitoring starts
+                if c.last_digest == 0 || send_digest {
+                    c.last_digest = now;
+                }
+            })
+            .await;
+
+        if send_digest && config.last_digest != 0 {
+            let config = state.config_manager.get_user_config(user_id).await;
+            send_digest_message(bot, state, user_id, &config).await;
+        }
+    }
+}
+
+fn classify(result: &Result<ValidatedNode, Error>, reference: Option<u64>, sync_tolerance: u64) -> WatchStatus {
+    match result {
+        Ok(node) => match reference {
+            Some(reference) if reference.abs_diff(node.block_number) > sync_tolerance => WatchStatus::Behind,
+            _ => WatchStatus::Up,
+        },
+        Err(Error::ChainIdMismatch { .. }) | Err(Error::GenesisMismatch) => WatchStatus::ChainChanged,
+        Err(_) => WatchStatus::Down,
+    }
+}
+
+async fn send_alert(bot: &Bot, user_id: i64, watched: &WatchedNode, status: WatchStatus) {
+    let msg = match status {
+        WatchStatus::Up => format!("✅ Watched node <code>{}</code> is back up.", html_escape(&watched.url)),
+        _ => format!(
+            "⚠️ Watched node <code>{}</code> is now {} (was {}).",
+            html_escape(&watched.url),
+            status.label(),
+            watched.status.label()
+        ),
+    };
+
+    if let Err(e) = bot
+        .send_message(ChatId(user_id), msg)
+        .parse_mode(teloxide::types::ParseMode::Html)
+        .await
+    {
+        log::warn!("Failed to send watchlist alert to {}: {}", user_id, e);
+    }
+}
+
+/// Summary of the watchlist plus known-good replacements for chains with
+/// unhealthy watched nodes
+async fn send_digest_message(bot: &Bot, state: &BotState, user_id: i64, config: &UserConfig) {
+    let now = store::now();
+    let up = config.watchlist.iter().filter(|w| w.status == WatchStatus::Up).count();
+    let mut msg = format!(
+        "📰 <b>Daily watchlist digest</b>\n\n{} of {} watched nodes are up.\n",
+        up,
+        config.watchlist.len()
+    );
+
+    let mut unhealthy_chains: Vec<u64> = Vec::new();
+    for watched in config.watchlist.iter().filter(|w| w.status != WatchStatus::Up) {
+        msg.push_str(&format!(
+            "\n{} <code>{}</code> for {}",
+            watched.status.label(),
+            html_escape(&watched.url),
+            format_duration(now.saturating_sub(watched.status_since))
+        ));
+        if !unhealthy_chains.contains(&watched.chain_id) {
+            unhealthy_chains.push(watched.chain_id);
+        }
+    }
+
+    if let Some(node_store) = &state.pipeline.store {
+        for chain_id in unhealthy_chains {
+            let Some(chain) = config.get_chain(chain_id) else {
+                continue;
+            };
+            let chain_name = chain.name.clone();
+            let mut request = SearchRequest {
+                chain,
+                node_type: NodeType::Full,
+                country_code: None,
+                config: config.clone(),
+            };
+            request.config.default_count = (DIGEST_REPLACEMENTS + config.watchlist.len()) as u32;
+
+            let replacements: Vec<_> = node_store
+                .known_good(&request, DIGEST_INTERVAL_SECS)
+                .await
+                .into_iter()
+                .filter(|r| !config.is_watching(chain_id, &r.node.url))
+                .take(DIGEST_REPLACEMENTS)
+                .collect();
+            if replacements.is_empty() {
+                continue;
+            }
+
+            msg.push_str(&format!("\n\n🔁 Replacements for <b>{}</b>:", chain_name));
+            for record in replacements {
+                msg.push_str(&format!("\n• <code>{}</code> ({} ms)", html_escape(&record.node.url), record.node.latency_ms));
+            }
+        }
+    }
+
+    if let Err(e) = bot
+        .send_message(ChatId(user_id), msg)
+        .parse_mode(teloxide::types::ParseMode::Html)
+        .await
+    {
+        log::warn!("Failed to send watchlist digest to {}: {}", user_id, e);
+    }
+}
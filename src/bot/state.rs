@@ -1,71 +1,91 @@
-use crate::chains::Chain;
-use crate::config::storage::ConfigManager;
-use crate::shodan::ShodanClient;
-use crate::validator::{archive::ArchiveValidator, http::HttpValidator, ws::WsValidator};
-use std::collections::HashMap;
+use node_finder::chains::Chain;
+use node_finder::config::storage::ConfigManager;
+use node_finder::discovery::import::ImportedList;
+use node_finder::discovery::Discovery;
+use node_finder::search::{NodeType, SearchDiagnostics, SearchPipeline};
+use node_finder::shodan::ShodanClient;
+use node_finder::store::NodeStore;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum NodeType {
-    Full,
-    Archive,
-    Bulk,
-}
-
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct UserSession {
     pub node_type: Option<NodeType>,
     pub chain: Option<Chain>,
     pub custom_chain_id: Option<u64>,
     pub awaiting_chain_id: bool,
     pub awaiting_rpc_url: bool,
+    pub awaiting_archive_block: bool,
+    /// Editing the Shodan filters of `custom_chain_id`
+    pub awaiting_shodan_query: bool,
+    /// Editing the excluded hosting providers
+    pub awaiting_providers: bool,
+    /// Editing a reference RPC from the config menu rather than the custom chain wizard
+    pub editing_rpc: bool,
+    /// Custom chain waiting for the user to confirm its fetched genesis hash
+    pub pending_chain: Option<Chain>,
+    /// Candidates from an uploaded list, validated instead of searching
+    pub imported: Option<Arc<ImportedList>>,
 }
 
-impl Default for UserSession {
-    fn default() -> Self {
-        Self {
-            node_type: None,
-            chain: None,
-            custom_chain_id: None,
-            awaiting_chain_id: false,
-            awaiting_rpc_url: false,
-        }
-    }
+/// Search reports kept for the "Details" and pin buttons; older ones are dropped
+const MAX_STORED_REPORTS: usize = 100;
+
+/// What a results message's buttons refer back to
+#[derive(Debug, Clone)]
+pub struct SearchReport {
+    pub chain_id: u64,
+    /// Result URLs in the order they were listed
+    pub urls: Vec<String>,
+    pub diagnostics: SearchDiagnostics,
 }
 
 #[derive(Clone)]
 pub struct BotState {
-    pub shodan: ShodanClient,
+    pub pipeline: SearchPipeline,
+    /// The discovery Shodan client, for facet counts before a search
+    pub shodan: Option<ShodanClient>,
     pub config_manager: ConfigManager,
-    pub http_validator: Arc<HttpValidator>,
-    pub ws_validator: Arc<WsValidator>,
-    pub archive_validator: Arc<ArchiveValidator>,
     pub sessions: Arc<RwLock<HashMap<i64, UserSession>>>,
+    reports: Arc<RwLock<VecDeque<(u64, SearchReport)>>>,
+    next_report_id: Arc<AtomicU64>,
 }
 
 impl BotState {
-    pub fn new(shodan_token: String) -> Self {
+    pub fn new(discovery: Discovery, shodan: Option<ShodanClient>) -> Self {
         Self {
-            shodan: ShodanClient::new(shodan_token),
+            pipeline: SearchPipeline::new(discovery).with_store(NodeStore::new()),
+            shodan,
             config_manager: ConfigManager::new(),
-            http_validator: Arc::new(HttpValidator::new()),
-            ws_validator: Arc::new(WsValidator::new()),
-            archive_validator: Arc::new(ArchiveValidator::new()),
             sessions: Arc::new(RwLock::new(HashMap::new())),
+            reports: Arc::new(RwLock::new(VecDeque::new())),
+            next_report_id: Arc::new(AtomicU64::new(1)),
         }
     }
 
+    /// Keep a search's report and return the id for its message buttons
+    pub async fn store_report(&self, report: SearchReport) -> u64 {
+        let id = self.next_report_id.fetch_add(1, Ordering::Relaxed);
+        let mut reports = self.reports.write().await;
+        if reports.len() >= MAX_STORED_REPORTS {
+            reports.pop_front();
+        }
+        reports.push_back((id, report));
+        id
+    }
+
+    pub async fn get_report(&self, id: u64) -> Option<SearchReport> {
+        let reports = self.reports.read().await;
+        reports.iter().find(|(report_id, _)| *report_id == id).map(|(_, r)| r.clone())
+    }
+
     pub async fn get_session(&self, user_id: i64) -> UserSession {
         let sessions = self.sessions.read().await;
         sessions.get(&user_id).cloned().unwrap_or_default()
     }
 
-    pub async fn set_session(&self, user_id: i64, session: UserSession) {
-        let mut sessions = self.sessions.write().await;
-        sessions.insert(user_id, session);
-    }
-
     pub async fn clear_session(&self, user_id: i64) {
         let mut sessions = self.sessions.write().await;
         sessions.remove(&user_id);
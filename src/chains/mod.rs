@@ -1,48 +1,111 @@
 pub mod genesis;
+pub mod registry;
 
 use serde::{Deserialize, Serialize};
 
+pub use registry::registry;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Chain {
     pub id: u64,
     pub name: String,
     pub symbol: String,
     pub default_rpc: String,
+    /// Reference RPCs tried in order when `default_rpc` is unreachable
+    #[serde(default)]
+    pub fallback_rpcs: Vec<String>,
+    /// Empty for chains whose genesis has not been fetched and confirmed yet
+    #[serde(default)]
     pub genesis_hash: String,
+    /// Heights checked first when searching for the oldest block with state
+    #[serde(default = "default_archive_probe_heights")]
+    pub archive_probe_heights: Vec<u64>,
+    /// Oldest block a node of the current client can hold state for, e.g.
+    /// the Nitro or Bedrock migration block. Older history lives only in the
+    /// legacy client, so archive searches stop here.
+    #[serde(default)]
+    pub first_state_block: u64,
+    #[serde(default = "default_block_time_ms")]
+    pub block_time_ms: u64,
+}
+
+fn default_archive_probe_heights() -> Vec<u64> {
+    vec![1]
+}
+
+fn default_block_time_ms() -> u64 {
+    12_000
 }
 
 impl Chain {
     pub fn hex_id(&self) -> String {
         format!("0x{:x}", self.id)
     }
+
+    /// Oldest block a node must serve state for to qualify as an archive node
+    /// when the user asks for state from `archive_from_block`
+    pub fn archive_bound(&self, archive_from_block: u64) -> u64 {
+        archive_from_block.max(self.first_state_block)
+    }
+
+    /// Default reference RPC followed by the fallbacks
+    pub fn reference_rpcs(&self) -> Vec<String> {
+        std::iter::once(self.default_rpc.clone())
+            .chain(self.fallback_rpcs.iter().cloned())
+            .collect()
+    }
+}
+
+/// Whether `hash` is a block hash: `0x` followed by 64 hex digits
+pub fn is_block_hash(hash: &str) -> bool {
+    hash.strip_prefix("0x")
+        .is_some_and(|hex| hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit()))
 }
 
-pub fn get_default_chains() -> Vec<Chain> {
+/// Chains compiled into the binary, used when no registry file is present
+pub fn builtin_chains() -> Vec<Chain> {
     vec![
         Chain {
             id: 1,
             name: "Ethereum".to_string(),
             symbol: "Ξ".to_string(),
             default_rpc: "https://eth.llamarpc.com".to_string(),
+            fallback_rpcs: vec!["https://ethereum-rpc.publicnode.com".to_string()],
             genesis_hash: genesis::ETH_GENESIS.to_string(),
+            archive_probe_heights: vec![1, 100, 1_000_000],
+            first_state_block: 0,
+            block_time_ms: 12_000,
         },
         Chain {
             id: 56,
             name: "BSC".to_string(),
             symbol: "⛓️".to_string(),
             default_rpc: "https://bsc.meowrpc.com".to_string(),
+            fallback_rpcs: vec!["https://bsc-rpc.publicnode.com".to_string()],
             genesis_hash: genesis::BSC_GENESIS.to_string(),
+            archive_probe_heights: vec![1, 100, 1_000_000],
+            first_state_block: 0,
+            block_time_ms: 3_000,
         },
         Chain {
             id: 8453,
             name: "Base".to_string(),
             symbol: "🔵".to_string(),
             default_rpc: "https://base-rpc.publicnode.com".to_string(),
+            fallback_rpcs: vec!["https://mainnet.base.org".to_string()],
             genesis_hash: genesis::BASE_GENESIS.to_string(),
+            archive_probe_heights: vec![1, 100, 1_000_000],
+            first_state_block: 0,
+            block_time_ms: 2_000,
         },
     ]
 }
 
+/// Chains offered in the chain selection menu
+pub fn get_chains() -> Vec<Chain> {
+    registry().chains().to_vec()
+}
+
 pub fn get_chain_by_id(id: u64) -> Option<Chain> {
-    get_default_chains().into_iter().find(|c| c.id == id)
+    registry().get(id).cloned()
 }
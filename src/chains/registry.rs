@@ -0,0 +1,148 @@
+use super::{builtin_chains, is_block_hash, Chain};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+const CHAINS_FILE: &str = "chains.json";
+
+static REGISTRY: OnceLock<ChainRegistry> = OnceLock::new();
+
+/// Chain registry loaded from `chains.json` (or `CHAINS_FILE`), optionally
+/// extended with an offline chainlist.org `chains.json` dump (`CHAINLIST_FILE`)
+pub fn registry() -> &'static ChainRegistry {
+    REGISTRY.get_or_init(ChainRegistry::load_or_builtin)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChainRegistry {
+    chains: Vec<Chain>,
+    /// Chains from a chainlist import, looked up by ID but not listed in menus
+    #[serde(skip)]
+    imported: Vec<Chain>,
+}
+
+/// Entry of chainlist.org's `chains.json`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChainlistEntry {
+    name: String,
+    chain_id: u64,
+    #[serde(default)]
+    rpc: Vec<serde_json::Value>,
+}
+
+impl ChainRegistry {
+    pub fn builtin() -> Self {
+        Self {
+            chains: builtin_chains(),
+            imported: Vec::new(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let registry: Self = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+        registry
+            .validate()
+            .map_err(|e| format!("Invalid {}: {}", path.display(), e))?;
+        Ok(registry)
+    }
+
+    /// Registry chains need unique IDs and a pinned `0x` + 64 hex digit genesis
+    fn validate(&self) -> Result<(), String> {
+        for (i, chain) in self.chains.iter().enumerate() {
+            if self.chains[..i].iter().any(|c| c.id == chain.id) {
+                return Err(format!("duplicate chain ID {}", chain.id));
+            }
+            if !is_block_hash(&chain.genesis_hash) {
+                return Err(format!(
+                    "{} (chain {}) has a malformed genesis hash {:?}",
+                    chain.name, chain.id, chain.genesis_hash
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Load the registry file, falling back to the built-in chains when it is
+    /// missing or invalid, then apply a chainlist import if configured
+    pub fn load_or_builtin() -> Self {
+        let path = env::var("CHAINS_FILE")
+            .ok()
+            .filter(|p| !p.is_empty())
+            .unwrap_or_else(|| CHAINS_FILE.to_string());
+        let mut registry = if Path::new(&path).exists() {
+            Self::load(Path::new(&path)).unwrap_or_else(|e| {
+                log::error!("{}, using built-in chains", e);
+                Self::builtin()
+            })
+        } else {
+            Self::builtin()
+        };
+
+        if let Some(chainlist) = env::var("CHAINLIST_FILE").ok().filter(|p| !p.is_empty()) {
+            match registry.import_chainlist(Path::new(&chainlist)) {
+                Ok(count) => log::info!("Imported {} chains from {}", count, chainlist),
+                Err(e) => log::error!("Chainlist import failed: {}", e),
+            }
+        }
+
+        registry
+    }
+
+    /// Import chains from a chainlist.org `chains.json` dump. Chains already in
+    /// the registry are kept as-is. Imported chains carry no genesis hash, so
+    /// users confirm one fetched from the reference RPC on first use.
+    pub fn import_chainlist(&mut self, path: &Path) -> Result<usize, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let entries: Vec<ChainlistEntry> = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+
+        let before = self.imported.len();
+        for entry in entries {
+            if self.get(entry.chain_id).is_some() {
+                continue;
+            }
+
+            // Skip WS endpoints and templates needing an API key ("${INFURA_API_KEY}")
+            let mut rpcs = entry.rpc.iter().filter_map(|rpc| {
+                let url = rpc.as_str().or_else(|| rpc.get("url").and_then(|u| u.as_str()))?;
+                (url.starts_with("http") && !url.contains("${")).then(|| url.to_string())
+            });
+
+            let Some(default_rpc) = rpcs.next() else {
+                continue;
+            };
+
+            self.imported.push(Chain {
+                id: entry.chain_id,
+                name: entry.name,
+                symbol: "🔗".to_string(),
+                default_rpc,
+                fallback_rpcs: rpcs.collect(),
+                genesis_hash: String::new(),
+                archive_probe_heights: vec![1],
+                first_state_block: 0,
+                block_time_ms: 12_000,
+            });
+        }
+
+        Ok(self.imported.len() - before)
+    }
+
+    pub fn chains(&self) -> &[Chain] {
+        &self.chains
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Chain> {
+        self.chains
+            .iter()
+            .chain(self.imported.iter())
+            .find(|c| c.id == id)
+    }
+}
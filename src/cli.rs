@@ -0,0 +1,557 @@
+use node_finder::chains::{get_chain_by_id, get_chains, Chain};
+use node_finder::config::{Protocol, UserConfig};
+use node_finder::discovery::import::{ImportedList, MAX_IMPORT_CANDIDATES};
+use node_finder::discovery::scanner::{Cidr, PortScanner, DEFAULT_SCAN_PORTS, MAX_SCAN_ADDRESSES};
+use node_finder::discovery::hosting::NetworkFilter;
+use node_finder::discovery::{Discovery, DiscoveryQuery};
+use node_finder::search::{NodeType, SearchDiagnostics, SearchPipeline, SearchRequest};
+use node_finder::shodan::client::DEFAULT_FACETS;
+use node_finder::shodan::{ShodanClient, ShodanQuery};
+use node_finder::store::NodeStore;
+use node_finder::validator::client::ClientFamily;
+use node_finder::validator::ValidatedNode;
+use std::io::Read;
+
+pub const USAGE: &str = "\
+Usage:
+  node_finder                     Run the Telegram bot
+  node_finder search [options]    Search and validate nodes, print results to stdout
+  node_finder chains              List chains in the registry
+  node_finder nodes [--chain <id>]
+                                  List nodes in the node database with their health history
+  node_finder help                Show this help
+
+Search options:
+  --chain <id>             Chain ID (required)
+  --type <type>            full | archive | bulk | trace (default: full)
+  --country <code>         Two-letter country code (default: all locations)
+  --protocol <proto>       http | ws (default: http)
+  --count <n>              Number of nodes to return (default: 10, bulk: 50,
+                           import/scan: every candidate)
+  --sync-tolerance <n>     Max blocks behind the reference (default: 50)
+  --client <family>        Only return geth | erigon | nethermind | besu | reth | bor
+  --archive-from <block>   Archive searches need state back to this block (default: 0)
+  --include-exposed        Keep nodes with unlocked accounts or admin/personal/miner APIs
+  --no-subscription-check  Keep WS nodes that do not stream newHeads
+  --valid-tls-only         Only return HTTPS/WSS nodes with a verifiable certificate
+  --ws-ports <list>        WS ports tried after the one discovered (default: 8546,8545)
+  --ws-paths <list>        WS paths tried on each port, empty for root (default: ,/ws)
+  --pages <n>              Max result pages to spend per discovery source (default: 3)
+  --shodan <filters>       Shodan filters for the chain, e.g.
+                           'port:8547 product:Nethermind asn:AS24940 org:Hetzner',
+                           or 'raw <query>' ({chain_id} and {chain_id_hex} are substituted)
+  --facets                 Print Shodan match counts per country, org and ASN for the
+                           query instead of searching (costs no query credits)
+  --network <kind>         any | residential | hosting: only search these networks,
+                           judged by Shodan's org/ISP/cloud data (default: any)
+  --exclude-providers <list>
+                           Skip candidates of these providers before validation:
+                           short names (aws, gcp, hetzner, ...), ASNs or org text
+  --rpc <url>              Reference RPC, replacing the registry's RPCs (repeatable)
+  --genesis <hash>         Genesis hash for chains without one in the registry
+  --import <file>          Validate candidates from a file (- for stdin) instead of
+                           searching: ip:port lines, masscan JSON or list output,
+                           or http/https/ws/wss URLs
+  --scan <cidr,...>        Scan trusted ranges for open RPC ports instead of searching
+                           (at most 65536 addresses)
+  --scan-ports <list>      Ports to scan (default: 8545,8546,8547,9650,443)
+  --scan-rate <n>          TCP connections started per second (default: 500)
+  --format <fmt>           json | text (default: text)
+
+Requires at least one discovery source: SHODAN_TOKEN, CENSYS_API_ID and
+CENSYS_API_SECRET, FOFA_KEY or ZOOMEYE_API_KEY, unless --import or --scan is given.
+TELEGRAM_TOKEN is not needed.";
+
+pub const NO_SOURCE_CONFIGURED: &str =
+    "no discovery source configured: set SHODAN_TOKEN, CENSYS_API_ID and CENSYS_API_SECRET, FOFA_KEY or ZOOMEYE_API_KEY";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Json,
+    Text,
+}
+
+#[derive(Debug)]
+pub struct SearchArgs {
+    pub chain_id: u64,
+    pub node_type: NodeType,
+    pub country_code: Option<String>,
+    pub format: OutputFormat,
+    pub genesis_hash: Option<String>,
+    /// File of candidates to validate instead of searching, `-` for stdin
+    pub import: Option<String>,
+    /// Ranges to scan instead of searching
+    pub scan: Vec<Cidr>,
+    pub scan_ports: Vec<u16>,
+    /// TCP connections per second, the scanner's default when `None`
+    pub scan_rate: Option<u32>,
+    /// `--count`, when given
+    pub count: Option<u32>,
+    /// Print Shodan facet counts instead of searching
+    pub facets: bool,
+    pub config: UserConfig,
+}
+
+/// Run a CLI subcommand and return the process exit code
+pub async fn run(args: &[String]) -> i32 {
+    match args.first().map(String::as_str) {
+        Some("search") => match parse_search_args(&args[1..]) {
+            Ok(search_args) => run_search(search_args).await,
+            Err(e) => {
+                eprintln!("error: {}\n\n{}", e, USAGE);
+                2
+            }
+        },
+        Some("chains") => {
+            for chain in get_chains() {
+                println!("{}\t{}\t{}", chain.id, chain.name, chain.default_rpc);
+            }
+            0
+        }
+        Some("nodes") => match parse_nodes_args(&args[1..]) {
+            Ok(chain_id) => {
+                list_nodes(chain_id).await;
+                0
+            }
+            Err(e) => {
+                eprintln!("error: {}\n\n{}", e, USAGE);
+                2
+            }
+        },
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", USAGE);
+            0
+        }
+        Some(other) => {
+            eprintln!("error: unknown command '{}'\n\n{}", other, USAGE);
+            2
+        }
+        None => {
+            println!("{}", USAGE);
+            0
+        }
+    }
+}
+
+pub fn parse_search_args(args: &[String]) -> Result<SearchArgs, String> {
+    let mut chain_id = None;
+    let mut node_type = NodeType::Full;
+    let mut country_code = None;
+    let mut format = OutputFormat::Text;
+    let mut genesis_hash = None;
+    let mut count = None;
+    let mut import = None;
+    let mut shodan_query = None;
+    let mut scan = Vec::new();
+    let mut scan_ports = DEFAULT_SCAN_PORTS.to_vec();
+    let mut scan_rate = None;
+    let mut facets = false;
+    let mut config = UserConfig::default();
+
+    let mut iter = args.iter();
+    while let Some(flag) = iter.next() {
+        if flag == "--include-exposed" {
+            config.exclude_exposed = false;
+            continue;
+        }
+        if flag == "--no-subscription-check" {
+            config.check_subscriptions = false;
+            continue;
+        }
+        if flag == "--valid-tls-only" {
+            config.valid_tls_only = true;
+            continue;
+        }
+        if flag == "--facets" {
+            facets = true;
+            continue;
+        }
+
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("missing value for {}", flag))?;
+
+        match flag.as_str() {
+            "--chain" => chain_id = Some(parse_number(flag, value)?),
+            "--type" => {
+                node_type = NodeType::from_code(value)
+                    .ok_or_else(|| format!("unknown node type '{}'", value))?
+            }
+            "--country" => {
+                if value != "all" {
+                    country_code = Some(value.to_uppercase());
+                }
+            }
+            "--protocol" => {
+                config.protocol = match value.as_str() {
+                    "http" => Protocol::Http,
+                    "ws" => Protocol::Ws,
+                    _ => return Err(format!("unknown protocol '{}'", value)),
+                }
+            }
+            "--count" => count = Some(parse_u32(flag, value)?),
+            "--sync-tolerance" => config.sync_tolerance = parse_number(flag, value)?,
+            "--client" => {
+                config.client_filter = Some(
+                    ClientFamily::from_code(&value.to_lowercase())
+                        .ok_or_else(|| format!("unknown client '{}'", value))?,
+                )
+            }
+            "--archive-from" => config.archive_from_block = parse_number(flag, value)?,
+            "--ws-ports" => config.ws_ports = parse_ports(value)?,
+            "--ws-paths" => config.ws_paths = value.split(',').map(|p| p.trim().to_string()).collect(),
+            "--pages" => config.shodan_max_pages = parse_u32(flag, value)?.max(1),
+            "--rpc" => {
+                if !(value.starts_with("http://") || value.starts_with("https://")) {
+                    return Err(format!("invalid RPC URL '{}'", value));
+                }
+                let id = chain_id.ok_or("--rpc must come after --chain")?;
+                config.reference_rpcs.entry(id).or_default().push(value.clone());
+            }
+            "--genesis" => genesis_hash = Some(value.to_lowercase()),
+            "--import" => import = Some(value.clone()),
+            "--shodan" => shodan_query = Some(ShodanQuery::parse(value).map_err(|e| format!("--shodan: {}", e))?),
+            "--network" => {
+                config.network_filter = NetworkFilter::from_code(value)
+                    .ok_or_else(|| format!("unknown network kind '{}'", value))?
+            }
+            "--exclude-providers" => {
+                config.excluded_providers = value
+                    .split(',')
+                    .map(|p| p.trim().to_lowercase())
+                    .filter(|p| !p.is_empty())
+                    .collect()
+            }
+            "--scan" => {
+                for range in value.split(',').filter(|r| !r.trim().is_empty()) {
+                    scan.push(range.parse::<Cidr>()?);
+                }
+            }
+            "--scan-ports" => scan_ports = parse_ports(value)?,
+            "--scan-rate" => scan_rate = Some(parse_u32(flag, value)?),
+            "--format" => {
+                format = match value.as_str() {
+                    "json" => OutputFormat::Json,
+                    "text" => OutputFormat::Text,
+                    _ => return Err(format!("unknown format '{}'", value)),
+                }
+            }
+            _ => return Err(format!("unknown option '{}'", flag)),
+        }
+    }
+
+    let chain_id = chain_id.ok_or("--chain is required")?;
+    if let Some(query) = shodan_query {
+        config.shodan_queries.insert(chain_id, query);
+    }
+
+    let scan_addresses = scan.iter().fold(0u128, |sum, r| sum.saturating_add(r.len()));
+    if scan_addresses > MAX_SCAN_ADDRESSES {
+        return Err(format!(
+            "--scan covers {} addresses, more than the limit of {}",
+            scan_addresses, MAX_SCAN_ADDRESSES
+        ));
+    }
+
+    // Bulk is a formatting choice in the bot; here it is a larger full-node search
+    if node_type == NodeType::Bulk {
+        node_type = NodeType::Full;
+        config.default_count = count.unwrap_or(50);
+    } else if let Some(count) = count {
+        config.default_count = count;
+    }
+
+    Ok(SearchArgs {
+        chain_id,
+        node_type,
+        country_code,
+        format,
+        genesis_hash,
+        import,
+        scan,
+        scan_ports,
+        scan_rate,
+        count,
+        facets,
+        config,
+    })
+}
+
+fn parse_nodes_args(args: &[String]) -> Result<Option<u64>, String> {
+    match args {
+        [] => Ok(None),
+        [flag, value] if flag == "--chain" => Ok(Some(parse_number(flag, value)?)),
+        [flag, ..] => Err(format!("unknown option '{}'", flag)),
+    }
+}
+
+/// Comma-separated ports, e.g. `8545,8546`
+fn parse_ports(value: &str) -> Result<Vec<u16>, String> {
+    value
+        .split(',')
+        .filter(|p| !p.is_empty())
+        .map(|p| p.trim().parse().map_err(|_| format!("invalid port '{}'", p)))
+        .collect()
+}
+
+fn parse_number(flag: &str, value: &str) -> Result<u64, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} expects a number, got '{}'", flag, value))
+}
+
+fn parse_u32(flag: &str, value: &str) -> Result<u32, String> {
+    u32::try_from(parse_number(flag, value)?).map_err(|_| format!("{} expects a number up to {}, got '{}'", flag, u32::MAX, value))
+}
+
+/// Resolve the chain to search, from the registry or from --rpc/--genesis
+fn resolve_chain(args: &SearchArgs) -> Result<Chain, String> {
+    let mut chain = match get_chain_by_id(args.chain_id) {
+        Some(chain) => chain,
+        None => {
+            let rpc = args
+                .config
+                .get_reference_rpcs(args.chain_id)
+                .first()
+                .ok_or_else(|| format!("chain {} is not in the registry, pass --rpc and --genesis", args.chain_id))?;
+            Chain {
+                id: args.chain_id,
+                name: format!("Chain {}", args.chain_id),
+                symbol: "🔧".to_string(),
+                default_rpc: rpc.clone(),
+                fallback_rpcs: Vec::new(),
+                genesis_hash: String::new(),
+                archive_probe_heights: vec![1],
+                first_state_block: 0,
+                block_time_ms: 12_000,
+            }
+        }
+    };
+
+    if let Some(genesis) = &args.genesis_hash {
+        chain.genesis_hash = genesis.clone();
+    }
+    if chain.genesis_hash.is_empty() {
+        return Err(format!("no genesis hash known for chain {}, pass --genesis", chain.id));
+    }
+
+    Ok(chain)
+}
+
+/// Candidates from `--import`, read from a file or stdin
+fn read_import(path: &str) -> Result<ImportedList, String> {
+    let text = if path == "-" {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|e| format!("failed to read stdin: {}", e))?;
+        text
+    } else {
+        std::fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path, e))?
+    };
+
+    let list = ImportedList::parse(&text);
+    eprintln!(
+        "import: {} candidates, {} unreadable lines, {} over the {} limit",
+        list.len(),
+        list.skipped,
+        list.dropped,
+        MAX_IMPORT_CANDIDATES
+    );
+    if list.is_empty() {
+        return Err(format!("no candidates found in {}", path));
+    }
+    Ok(list)
+}
+
+async fn run_search(mut args: SearchArgs) -> i32 {
+    if args.facets {
+        return print_facets(&args).await;
+    }
+
+    // Imported lists and scans replace the search services
+    let mut discovery = Discovery::new();
+    let mut own_candidates: u128 = 0;
+    if let Some(path) = &args.import {
+        match read_import(path) {
+            Ok(list) => {
+                own_candidates += list.len() as u128;
+                discovery = discovery.with_source(list);
+            }
+            Err(e) => {
+                eprintln!("error: {}", e);
+                return 1;
+            }
+        }
+    }
+    if !args.scan.is_empty() {
+        let mut scanner = PortScanner::new(args.scan.clone()).with_ports(args.scan_ports.clone());
+        if let Some(rate) = args.scan_rate {
+            scanner = scanner.with_rate(rate);
+        }
+        eprintln!(
+            "scan: {} addresses on {} ports",
+            scanner.address_count(),
+            args.scan_ports.len()
+        );
+        own_candidates += scanner.target_count();
+        discovery = discovery.with_source(scanner);
+    }
+
+    if discovery.is_empty() {
+        discovery = Discovery::from_env();
+    } else if args.count.is_none() {
+        // Validate every imported candidate and scan hit unless asked for fewer
+        args.config.default_count = own_candidates.min(u32::MAX as u128) as u32;
+    }
+    if discovery.is_empty() {
+        eprintln!("error: {}", NO_SOURCE_CONFIGURED);
+        return 1;
+    }
+
+    let chain = match resolve_chain(&args) {
+        Ok(chain) => chain,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return 2;
+        }
+    };
+
+    let request = SearchRequest {
+        chain,
+        node_type: args.node_type,
+        country_code: args.country_code,
+        config: args.config,
+    };
+
+    // Record outcomes in the same database `node_finder nodes` lists
+    let pipeline = SearchPipeline::new(discovery).with_store(NodeStore::new());
+    let result = pipeline.search(&request).await;
+
+    match result {
+        Ok(outcome) => {
+            print_nodes(&outcome.nodes, args.format);
+            print_diagnostics(&outcome.diagnostics);
+            0
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            1
+        }
+    }
+}
+
+/// Shodan match counts as tab-separated `facet value count` lines
+async fn print_facets(args: &SearchArgs) -> i32 {
+    let Some(shodan) = ShodanClient::from_env() else {
+        eprintln!("error: --facets needs SHODAN_TOKEN");
+        return 1;
+    };
+
+    let query = DiscoveryQuery {
+        chain_id: args.chain_id,
+        country_code: args.country_code.clone(),
+        min_results: 0,
+        max_pages: 0,
+        shodan: args.config.get_shodan_query(args.chain_id),
+    };
+    match shodan.facets(&query, &DEFAULT_FACETS).await {
+        Ok(facets) => {
+            println!("total\t\t{}", facets.total);
+            for (facet, values) in &facets.facets {
+                for (value, count) in values {
+                    println!("{}\t{}\t{}", facet, value, count);
+                }
+            }
+            0
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            1
+        }
+    }
+}
+
+fn print_nodes(nodes: &[ValidatedNode], format: OutputFormat) {
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(nodes).unwrap_or_default());
+        }
+        OutputFormat::Text => {
+            for node in nodes {
+                println!("{}", node.url);
+            }
+        }
+    }
+}
+
+/// Funnel summary on stderr so stdout stays machine-readable
+fn print_diagnostics(d: &SearchDiagnostics) {
+    match (d.reference_block, d.reference_fallback) {
+        (Some(block), false) => eprintln!("reference: block {} ({} RPCs answered)", block, d.references_responded),
+        (Some(block), true) => eprintln!("reference: unreachable, using median candidate block {}", block),
+        (None, _) => eprintln!("reference: unreachable"),
+    }
+    if let Some(block) = d.checkpoint_disputed {
+        eprintln!("checkpoint: reference RPCs disagree on block {} with no majority, fork check skipped", block);
+    }
+    for (source, report) in &d.sources {
+        match &report.error {
+            Some(error) => eprintln!("{}: failed: {}", source, error),
+            None => eprintln!(
+                "{}: {} candidates ({} total, {} pages)",
+                source, report.candidates, report.total, report.pages
+            ),
+        }
+    }
+    eprintln!(
+        "candidates: {} unique ({} duplicates), {} off-port, {} provider-filtered, {} over budget",
+        d.candidates_found, d.duplicates, d.filtered_by_port, d.filtered_by_provider, d.over_budget
+    );
+    for (reason, count) in &d.rejections {
+        eprintln!("rejected {}: {}", reason, count);
+    }
+    eprintln!(
+        "filtered: {} client, {} no valid tls, {} exposed, {} no subscription, {} not archive, {} no trace",
+        d.client_filtered,
+        d.no_valid_tls,
+        d.exposed_excluded,
+        d.no_subscription,
+        d.not_archive,
+        d.no_trace_support
+    );
+    for (endpoint, count) in &d.ws_endpoints {
+        eprintln!("ws endpoint {}: {}", endpoint, count);
+    }
+    for (provider, count) in &d.providers {
+        eprintln!("provider {}: {}", provider, count);
+    }
+    eprintln!("passed: {}, returned: {}", d.passed, d.returned);
+}
+
+/// Print the node database as tab-separated columns, longest-lived first
+async fn list_nodes(chain_id: Option<u64>) {
+    let store = NodeStore::new();
+    let mut records = match chain_id {
+        Some(id) => store.records(id).await,
+        None => store.all_records().await,
+    };
+    records.sort_by_key(|r| std::cmp::Reverse(r.lifetime()));
+
+    println!("chain\turl\tstatus\tfirst_seen\tlast_seen\tlifetime_h\tchecks\tsuccess\tavg_ms\tclient");
+    for r in records {
+        println!(
+            "{}\t{}\t{}\t{}\t{}\t{:.1}\t{}\t{:.0}%\t{}\t{}",
+            r.chain_id,
+            r.node.url,
+            if r.is_healthy() { "up" } else { "down" },
+            r.first_seen,
+            r.last_seen,
+            r.lifetime() as f64 / 3600.0,
+            r.checks.len(),
+            r.success_rate() * 100.0,
+            r.average_latency_ms().map(|ms| ms.to_string()).unwrap_or_else(|| "-".to_string()),
+            r.node.client.as_ref().map(|c| c.label()).unwrap_or_else(|| "-".to_string()),
+        );
+    }
+}
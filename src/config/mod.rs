@@ -1,14 +1,165 @@
 pub mod storage;
 
+use crate::chains::{get_chain_by_id, Chain};
+use crate::discovery::hosting::{HostInfo, NetworkFilter};
+use crate::shodan::ShodanQuery;
+use crate::validator::capabilities::DEFAULT_CAPABILITY_METHODS;
+use crate::validator::client::{ClientFamily, ClientVersion};
 use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserConfig {
     pub default_count: u32,
     pub protocol: Protocol,
     pub sync_tolerance: u64,
-    pub reference_rpcs: HashMap<u64, String>,
+    /// Reference RPCs per chain, replacing the registry's. Older configs
+    /// stored a single URL per chain.
+    #[serde(deserialize_with = "deserialize_reference_rpcs")]
+    pub reference_rpcs: HashMap<u64, Vec<String>>,
+    /// Shodan filters per chain, changing the default query
+    #[serde(default)]
+    pub shodan_queries: HashMap<u64, ShodanQuery>,
+    /// Maximum result pages (query credits) spent per discovery source per search
+    #[serde(default = "default_shodan_max_pages")]
+    pub shodan_max_pages: u32,
+    /// Only return nodes running this client family (any client when unset)
+    #[serde(default)]
+    pub client_filter: Option<ClientFamily>,
+    /// Only search residential or only hosting networks
+    #[serde(default)]
+    pub network_filter: NetworkFilter,
+    /// Providers whose candidates are skipped before validation, by short
+    /// name (`aws`), ASN or org text
+    #[serde(default)]
+    pub excluded_providers: Vec<String>,
+    /// RPC methods probed on every result to detect debug_/trace_/txpool_ support
+    #[serde(default = "default_capability_methods")]
+    pub capability_methods: Vec<String>,
+    /// Drop nodes with unlocked accounts or admin/personal/miner namespaces
+    #[serde(default = "default_exclude_exposed")]
+    pub exclude_exposed: bool,
+    /// Ports tried for WebSocket after the one discovery found; `wss://` on 443 is always tried
+    #[serde(default = "default_ws_ports")]
+    pub ws_ports: Vec<u16>,
+    /// URL paths tried on every WebSocket port ("" for the root)
+    #[serde(default = "default_ws_paths")]
+    pub ws_paths: Vec<String>,
+    /// Only return HTTPS/WSS nodes whose certificate a verifying client accepts
+    #[serde(default)]
+    pub valid_tls_only: bool,
+    /// WebSocket searches only return nodes that stream `newHeads`
+    #[serde(default = "default_check_subscriptions")]
+    pub check_subscriptions: bool,
+    /// Archive searches require state back to this block (0 = full archive)
+    #[serde(default)]
+    pub archive_from_block: u64,
+    /// Custom chains with their confirmed (pinned) genesis hash
+    #[serde(default)]
+    pub custom_chains: HashMap<u64, Chain>,
+    /// Nodes pinned from search results and monitored for failures
+    #[serde(default)]
+    pub watchlist: Vec<WatchedNode>,
+    /// Unix time the last daily watchlist digest was sent
+    #[serde(default)]
+    pub last_digest: u64,
+}
+
+/// Health of a watched node as of its last check
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchStatus {
+    #[default]
+    Up,
+    /// Further behind the reference than the user's sync tolerance
+    Behind,
+    /// Answering with a different chain ID or genesis hash
+    ChainChanged,
+    /// Not responding or returning errors
+    Down,
+}
+
+impl WatchStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            WatchStatus::Up => "✅ up",
+            WatchStatus::Behind => "🐢 out of sync",
+            WatchStatus::ChainChanged => "🔀 chain/genesis changed",
+            WatchStatus::Down => "💀 down",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchedNode {
+    pub url: String,
+    pub chain_id: u64,
+    pub added_at: u64,
+    #[serde(default)]
+    pub status: WatchStatus,
+    /// Unix time the status last changed
+    #[serde(default)]
+    pub status_since: u64,
+    #[serde(default)]
+    pub last_checked: u64,
+}
+
+impl WatchedNode {
+    /// Identifier of the entry that fits in Telegram's 64-byte callback data
+    /// and stays valid while other entries are added or removed: the chain ID
+    /// and a hash of the URL
+    pub fn key(&self) -> String {
+        let mut hasher = DefaultHasher::new();
+        self.url.hash(&mut hasher);
+        format!("{}:{:x}", self.chain_id, hasher.finish())
+    }
+}
+
+fn deserialize_reference_rpcs<'de, D>(deserializer: D) -> Result<HashMap<u64, Vec<String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    let map = HashMap::<u64, OneOrMany>::deserialize(deserializer)?;
+    Ok(map
+        .into_iter()
+        .map(|(id, rpcs)| match rpcs {
+            OneOrMany::One(rpc) => (id, vec![rpc]),
+            OneOrMany::Many(rpcs) => (id, rpcs),
+        })
+        .collect())
+}
+
+fn default_ws_ports() -> Vec<u16> {
+    vec![8546, 8545]
+}
+
+fn default_ws_paths() -> Vec<String> {
+    vec![String::new(), "/ws".to_string()]
+}
+
+fn default_check_subscriptions() -> bool {
+    true
+}
+
+fn default_exclude_exposed() -> bool {
+    true
+}
+
+fn default_capability_methods() -> Vec<String> {
+    DEFAULT_CAPABILITY_METHODS.iter().map(|m| m.to_string()).collect()
+}
+
+fn default_shodan_max_pages() -> u32 {
+    3
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
@@ -20,22 +171,75 @@ pub enum Protocol {
 
 impl Default for UserConfig {
     fn default() -> Self {
-        let mut reference_rpcs = HashMap::new();
-        reference_rpcs.insert(1, "https://eth.llamarpc.com".to_string());
-        reference_rpcs.insert(56, "https://bsc.meowrpc.com".to_string());
-        reference_rpcs.insert(8453, "https://base-rpc.publicnode.com".to_string());
-
         Self {
             default_count: 10,
             protocol: Protocol::Http,
             sync_tolerance: 50,
-            reference_rpcs,
+            // Empty means the chain registry's default and fallback RPCs are used
+            reference_rpcs: HashMap::new(),
+            shodan_queries: HashMap::new(),
+            shodan_max_pages: default_shodan_max_pages(),
+            client_filter: None,
+            network_filter: NetworkFilter::Any,
+            excluded_providers: Vec::new(),
+            capability_methods: default_capability_methods(),
+            exclude_exposed: default_exclude_exposed(),
+            ws_ports: default_ws_ports(),
+            ws_paths: default_ws_paths(),
+            valid_tls_only: false,
+            check_subscriptions: default_check_subscriptions(),
+            archive_from_block: 0,
+            custom_chains: HashMap::new(),
+            watchlist: Vec::new(),
+            last_digest: 0,
         }
     }
 }
 
 impl UserConfig {
-    pub fn get_reference_rpc(&self, chain_id: u64) -> Option<&String> {
-        self.reference_rpcs.get(&chain_id)
+    /// Whether a node's fingerprinted client passes the user's client filter
+    pub fn accepts_client(&self, client: Option<&ClientVersion>) -> bool {
+        match self.client_filter {
+            None => true,
+            Some(family) => client.is_some_and(|c| c.family == family),
+        }
+    }
+
+    /// Whether a candidate's host passes the network filter and provider
+    /// exclusions; without host metadata only the `Any` filter passes
+    pub fn accepts_host(&self, host: Option<&HostInfo>) -> bool {
+        self.network_filter.accepts(host)
+            && !host.is_some_and(|h| self.excluded_providers.iter().any(|p| h.matches_provider(p)))
+    }
+
+    /// The user's reference RPCs for a chain, empty when the registry's are used
+    pub fn get_reference_rpcs(&self, chain_id: u64) -> &[String] {
+        self.reference_rpcs.get(&chain_id).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// The user's Shodan filters for a chain, empty for the default query
+    pub fn get_shodan_query(&self, chain_id: u64) -> ShodanQuery {
+        self.shodan_queries.get(&chain_id).cloned().unwrap_or_default()
+    }
+
+    /// Registry chain, with the genesis hash the user pinned if the registry
+    /// has none, otherwise the user's custom chain
+    pub fn get_chain(&self, chain_id: u64) -> Option<Chain> {
+        let pinned = self.custom_chains.get(&chain_id);
+        match get_chain_by_id(chain_id) {
+            Some(mut chain) => {
+                if chain.genesis_hash.is_empty() {
+                    if let Some(pinned) = pinned {
+                        chain.genesis_hash = pinned.genesis_hash.clone();
+                    }
+                }
+                Some(chain)
+            }
+            None => pinned.cloned(),
+        }
+    }
+
+    pub fn is_watching(&self, chain_id: u64, url: &str) -> bool {
+        self.watchlist.iter().any(|w| w.chain_id == chain_id && w.url == url)
     }
 }
@@ -39,6 +39,12 @@ impl ConfigManager {
         store.users.get(&user_id).cloned().unwrap_or_default()
     }
 
+    /// Snapshot of every stored user's config
+    pub async fn all_user_configs(&self) -> Vec<(i64, UserConfig)> {
+        let store = self.store.read().await;
+        store.users.iter().map(|(id, config)| (*id, config.clone())).collect()
+    }
+
     pub async fn set_user_config(&self, user_id: i64, config: UserConfig) {
         let mut store = self.store.write().await;
         store.users.insert(user_id, config);
@@ -0,0 +1,215 @@
+use super::{Candidate, CertificateInfo, DiscoveryQuery, DiscoveryResult, DiscoverySource, RateLimiter, RPC_PORTS};
+use crate::error::Error;
+use futures::future::BoxFuture;
+use reqwest::Client;
+use serde::Deserialize;
+use std::time::Duration;
+
+const CENSYS_API_BASE: &str = "https://search.censys.io/api";
+/// Free accounts are limited to 0.4 requests per second
+const RATE_LIMIT_INTERVAL: Duration = Duration::from_millis(2500);
+const CENSYS_PAGE_SIZE: usize = 100;
+
+#[derive(Debug, Deserialize)]
+struct CensysResponse {
+    result: CensysResult,
+}
+
+#[derive(Debug, Deserialize)]
+struct CensysResult {
+    #[serde(default)]
+    total: u64,
+    #[serde(default)]
+    hits: Vec<CensysHit>,
+    #[serde(default)]
+    links: CensysLinks,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CensysLinks {
+    #[serde(default)]
+    next: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CensysHit {
+    ip: String,
+    /// Services that matched the query; older responses only carry `services`
+    #[serde(default)]
+    matched_services: Vec<CensysService>,
+    #[serde(default)]
+    services: Vec<CensysService>,
+    location: Option<CensysLocation>,
+    dns: Option<CensysDns>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CensysService {
+    port: u16,
+    #[serde(default)]
+    extended_service_name: String,
+    certificate: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CensysLocation {
+    country_code: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CensysDns {
+    reverse_dns: Option<CensysReverseDns>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CensysReverseDns {
+    #[serde(default)]
+    names: Vec<String>,
+}
+
+impl CensysHit {
+    /// One candidate per matched RPC port
+    fn into_candidates(self) -> Vec<Candidate> {
+        let services = if self.matched_services.is_empty() {
+            self.services
+        } else {
+            self.matched_services
+        };
+        let country_code = self.location.and_then(|l| l.country_code);
+        let hostnames = self
+            .dns
+            .and_then(|d| d.reverse_dns)
+            .map(|r| r.names)
+            .unwrap_or_default();
+
+        services
+            .into_iter()
+            .filter(|s| RPC_PORTS.contains(&s.port))
+            .map(|service| {
+                let mut candidate = Candidate::new("censys", self.ip.clone(), service.port);
+                candidate.country_code = country_code.clone();
+                candidate.add_hostnames(hostnames.iter().cloned());
+                if service.extended_service_name == "HTTPS" || service.certificate.is_some() {
+                    candidate.certificate = Some(CertificateInfo::default());
+                }
+                candidate
+            })
+            .collect()
+    }
+}
+
+/// Censys Search v2 hosts API, authenticated with an API ID and secret
+#[derive(Debug, Clone)]
+pub struct CensysClient {
+    client: Client,
+    api_id: String,
+    api_secret: String,
+    base_url: String,
+    rate_limiter: RateLimiter,
+}
+
+impl CensysClient {
+    pub fn new(api_id: String, api_secret: String) -> Self {
+        Self {
+            client: Client::new(),
+            api_id,
+            api_secret,
+            base_url: CENSYS_API_BASE.to_string(),
+            rate_limiter: RateLimiter::new(RATE_LIMIT_INTERVAL),
+        }
+    }
+
+    /// Send requests to another API root, e.g. a proxy or a test server
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    fn error(message: String) -> Error {
+        Error::Discovery {
+            provider: "censys",
+            message,
+        }
+    }
+
+    /// Censys does not index the chain ID, so this matches JSON-RPC servers
+    /// of every chain; the search pre-checks their chain ID before validation
+    fn build_query(country_code: Option<&str>) -> String {
+        let ports: Vec<String> = RPC_PORTS.iter().map(|p| p.to_string()).collect();
+        let mut query = format!(
+            "services: (port: {{{}}} and http.response.body: \"jsonrpc\")",
+            ports.join(", ")
+        );
+
+        if let Some(cc) = country_code {
+            query.push_str(&format!(" and location.country_code: {}", cc));
+        }
+
+        query
+    }
+
+    pub async fn search_nodes(&self, query: &DiscoveryQuery) -> Result<DiscoveryResult, Error> {
+        let censys_query = Self::build_query(query.country_code.as_deref());
+
+        let mut search = DiscoveryResult::default();
+        let mut cursor = String::new();
+
+        while search.pages_fetched < query.max_pages.max(1) {
+            let data = self.fetch_page(&censys_query, &cursor).await?;
+            search.pages_fetched += 1;
+            search.total = data.total;
+
+            let page_len = data.hits.len();
+            search
+                .candidates
+                .extend(data.hits.into_iter().flat_map(CensysHit::into_candidates));
+
+            cursor = data.links.next;
+            if cursor.is_empty() || page_len < CENSYS_PAGE_SIZE || search.candidates.len() >= query.min_results {
+                break;
+            }
+        }
+
+        Ok(search)
+    }
+
+    async fn fetch_page(&self, query: &str, cursor: &str) -> Result<CensysResult, Error> {
+        self.rate_limiter.wait().await;
+
+        let mut params = vec![("q", query.to_string()), ("per_page", CENSYS_PAGE_SIZE.to_string())];
+        if !cursor.is_empty() {
+            params.push(("cursor", cursor.to_string()));
+        }
+
+        let response = self
+            .client
+            .get(format!("{}/v2/hosts/search", self.base_url))
+            .query(&params)
+            .basic_auth(&self.api_id, Some(&self.api_secret))
+            .timeout(Duration::from_secs(30))
+            .send()
+            .await
+            .map_err(|e| Self::error(format!("request failed: {}", e.without_url())))?;
+
+        if !response.status().is_success() {
+            return Err(Self::error(format!("API error: {}", response.status())));
+        }
+
+        let data: CensysResponse = response
+            .json()
+            .await
+            .map_err(|e| Self::error(format!("failed to parse response: {}", e.without_url())))?;
+
+        Ok(data.result)
+    }
+}
+
+impl DiscoverySource for CensysClient {
+    fn name(&self) -> &'static str {
+        "censys"
+    }
+
+    fn search<'a>(&'a self, query: &'a DiscoveryQuery) -> BoxFuture<'a, Result<DiscoveryResult, Error>> {
+        Box::pin(self.search_nodes(query))
+    }
+}
@@ -0,0 +1,163 @@
+use super::{Candidate, CertificateInfo, DiscoveryQuery, DiscoveryResult, DiscoverySource, RateLimiter, RPC_PORTS};
+use crate::error::Error;
+use base64::Engine;
+use futures::future::BoxFuture;
+use reqwest::Client;
+use serde::Deserialize;
+use std::time::Duration;
+
+const FOFA_API_BASE: &str = "https://fofa.info";
+const RATE_LIMIT_INTERVAL: Duration = Duration::from_secs(1);
+const FOFA_PAGE_SIZE: usize = 100;
+/// Result columns requested, in the order each result row lists them
+const FOFA_FIELDS: &str = "ip,port,country,protocol,domain";
+
+#[derive(Debug, Deserialize)]
+struct FofaResponse {
+    #[serde(default)]
+    error: bool,
+    #[serde(default)]
+    errmsg: String,
+    /// Total matches for the query
+    #[serde(default)]
+    size: u64,
+    #[serde(default)]
+    results: Vec<Vec<String>>,
+}
+
+/// Turn one `FOFA_FIELDS` row into a candidate, skipping malformed rows
+fn parse_row(row: Vec<String>) -> Option<Candidate> {
+    let [ip, port, country, protocol, domain]: [String; 5] = row.try_into().ok()?;
+    let port: u16 = port.parse().ok()?;
+
+    let mut candidate = Candidate::new("fofa", ip, port);
+    candidate.country_code = Some(country).filter(|c| !c.is_empty());
+    candidate.add_hostnames(Some(domain));
+    if protocol == "https" {
+        candidate.certificate = Some(CertificateInfo::default());
+    }
+    Some(candidate)
+}
+
+/// FOFA search API, authenticated with an API key (and the account email
+/// for accounts created before keys stood alone)
+#[derive(Debug, Clone)]
+pub struct FofaClient {
+    client: Client,
+    email: Option<String>,
+    key: String,
+    base_url: String,
+    rate_limiter: RateLimiter,
+}
+
+impl FofaClient {
+    pub fn new(email: Option<String>, key: String) -> Self {
+        Self {
+            client: Client::new(),
+            email,
+            key,
+            base_url: FOFA_API_BASE.to_string(),
+            rate_limiter: RateLimiter::new(RATE_LIMIT_INTERVAL),
+        }
+    }
+
+    /// Send requests to another API root, e.g. a proxy or a test server
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    fn error(message: String) -> Error {
+        Error::Discovery {
+            provider: "fofa",
+            message,
+        }
+    }
+
+    /// FOFA does not index the chain ID, so this matches JSON-RPC servers
+    /// of every chain; the search pre-checks their chain ID before validation
+    fn build_query(country_code: Option<&str>) -> String {
+        let ports: Vec<String> = RPC_PORTS.iter().map(|p| format!("port=\"{}\"", p)).collect();
+        let mut query = format!("({}) && body=\"jsonrpc\"", ports.join(" || "));
+
+        if let Some(cc) = country_code {
+            query.push_str(&format!(" && country=\"{}\"", cc));
+        }
+
+        query
+    }
+
+    pub async fn search_nodes(&self, query: &DiscoveryQuery) -> Result<DiscoveryResult, Error> {
+        let fofa_query = Self::build_query(query.country_code.as_deref());
+        let qbase64 = base64::engine::general_purpose::STANDARD.encode(fofa_query);
+
+        let mut search = DiscoveryResult::default();
+
+        while search.pages_fetched < query.max_pages.max(1) {
+            let page = search.pages_fetched + 1;
+            let data = self.fetch_page(&qbase64, page).await?;
+            search.pages_fetched = page;
+            search.total = data.size;
+
+            let page_len = data.results.len();
+            search.candidates.extend(data.results.into_iter().filter_map(parse_row));
+
+            let exhausted = page_len < FOFA_PAGE_SIZE || (page as u64 * FOFA_PAGE_SIZE as u64) >= search.total;
+            if exhausted || search.candidates.len() >= query.min_results {
+                break;
+            }
+        }
+
+        Ok(search)
+    }
+
+    async fn fetch_page(&self, qbase64: &str, page: u32) -> Result<FofaResponse, Error> {
+        self.rate_limiter.wait().await;
+
+        let mut params = vec![
+            ("key", self.key.clone()),
+            ("qbase64", qbase64.to_string()),
+            ("fields", FOFA_FIELDS.to_string()),
+            ("page", page.to_string()),
+            ("size", FOFA_PAGE_SIZE.to_string()),
+        ];
+        if let Some(email) = &self.email {
+            params.push(("email", email.clone()));
+        }
+
+        let response = self
+            .client
+            .get(format!("{}/api/v1/search/all", self.base_url))
+            .query(&params)
+            .timeout(Duration::from_secs(30))
+            .send()
+            .await
+            .map_err(|e| Self::error(format!("request failed: {}", e.without_url())))?;
+
+        if !response.status().is_success() {
+            return Err(Self::error(format!("API error: {}", response.status())));
+        }
+
+        let data: FofaResponse = response
+            .json()
+            .await
+            .map_err(|e| Self::error(format!("failed to parse response: {}", e.without_url())))?;
+
+        // FOFA reports errors such as a bad key or exhausted quota with status 200
+        if data.error {
+            return Err(Self::error(format!("API error: {}", data.errmsg)));
+        }
+
+        Ok(data)
+    }
+}
+
+impl DiscoverySource for FofaClient {
+    fn name(&self) -> &'static str {
+        "fofa"
+    }
+
+    fn search<'a>(&'a self, query: &'a DiscoveryQuery) -> BoxFuture<'a, Result<DiscoveryResult, Error>> {
+        Box::pin(self.search_nodes(query))
+    }
+}
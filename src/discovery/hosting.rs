@@ -0,0 +1,159 @@
+//! Who runs the network a candidate sits on, as reported by the discovery
+//! source, and the user's filters on it (residential only, no AWS, ...).
+
+use serde::{Deserialize, Serialize};
+
+/// Hosting and cloud providers recognised by name: the short name users
+/// type, then lowercase fragments of the org/ISP names the provider uses
+pub const HOSTING_PROVIDERS: &[(&str, &[&str])] = &[
+    ("aws", &["amazon"]),
+    ("gcp", &["google cloud", "google llc"]),
+    ("azure", &["microsoft"]),
+    ("digitalocean", &["digitalocean"]),
+    ("hetzner", &["hetzner"]),
+    ("ovh", &["ovh"]),
+    ("linode", &["linode", "akamai"]),
+    ("vultr", &["vultr", "choopa"]),
+    ("contabo", &["contabo"]),
+    ("alibaba", &["alibaba", "aliyun"]),
+    ("oracle", &["oracle"]),
+    ("tencent", &["tencent"]),
+    ("scaleway", &["scaleway", "online s.a.s"]),
+    ("leaseweb", &["leaseweb"]),
+    ("hostinger", &["hostinger"]),
+];
+
+/// Network and location details a source knows about a candidate's host
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HostInfo {
+    /// Organization the IP is assigned to, e.g. `Hetzner Online GmbH`
+    pub org: Option<String>,
+    /// Autonomous system, e.g. `AS24940`
+    pub asn: Option<String>,
+    pub isp: Option<String>,
+    pub city: Option<String>,
+    /// Hostnames the sources associate with the IP
+    #[serde(default)]
+    pub hostnames: Vec<String>,
+    /// Cloud provider the source attributes the IP to, e.g. `Amazon`
+    pub cloud: Option<String>,
+    /// Source tags such as `cloud`, `vpn` or `self-signed`
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Unix time the source last scanned the service
+    pub last_update: Option<u64>,
+    /// What the source's own JSON-RPC probe saw, `None` when it did not probe
+    pub rpc: Option<ScannedRpc>,
+}
+
+/// Shodan's `ethereum_rpc` banner details
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScannedRpc {
+    /// Client as reported by `web3_clientVersion`, e.g. `Geth`
+    pub client: Option<String>,
+    pub version: Option<String>,
+    /// Block height at the time of the scan
+    pub block_number: Option<u64>,
+}
+
+impl HostInfo {
+    /// Name to group nodes by: the cloud provider, otherwise the org or ISP
+    pub fn provider(&self) -> Option<&str> {
+        self.cloud
+            .as_deref()
+            .or(self.org.as_deref())
+            .or(self.isp.as_deref())
+    }
+
+    /// Provider and city for display, e.g. `Hetzner Online GmbH, Falkenstein`
+    pub fn label(&self) -> Option<String> {
+        match (self.provider(), self.city.as_deref()) {
+            (Some(provider), Some(city)) => Some(format!("{}, {}", provider, city)),
+            (Some(provider), None) => Some(provider.to_string()),
+            (None, city) => city.map(String::from),
+        }
+    }
+
+    /// Cloud or hosting network: attributed to a cloud by the source, or run
+    /// by one of `HOSTING_PROVIDERS`. Anything else is taken as residential
+    /// or business access.
+    pub fn is_hosting(&self) -> bool {
+        self.cloud.is_some()
+            || self.tags.iter().any(|t| t == "cloud")
+            || HOSTING_PROVIDERS
+                .iter()
+                .any(|(name, _)| self.matches_provider(name))
+    }
+
+    /// Whether the host belongs to `provider`: a short name from
+    /// `HOSTING_PROVIDERS`, an ASN, or text found in the org, ISP or cloud name
+    pub fn matches_provider(&self, provider: &str) -> bool {
+        let provider = provider.trim().to_lowercase();
+        if provider.is_empty() {
+            return false;
+        }
+        if self.asn.as_deref().is_some_and(|asn| asn.eq_ignore_ascii_case(&provider)) {
+            return true;
+        }
+
+        let fragments: Vec<&str> = match HOSTING_PROVIDERS.iter().find(|(name, _)| *name == provider) {
+            Some((_, fragments)) => fragments.to_vec(),
+            None => vec![provider.as_str()],
+        };
+        [&self.org, &self.isp, &self.cloud]
+            .into_iter()
+            .flatten()
+            .map(|name| name.to_lowercase())
+            .any(|name| fragments.iter().any(|f| name.contains(f)))
+    }
+}
+
+/// Which networks searches keep, judged by the source's host metadata
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NetworkFilter {
+    #[default]
+    Any,
+    /// Only hosts outside cloud and hosting networks
+    Residential,
+    /// Only cloud and hosting networks
+    Hosting,
+}
+
+impl NetworkFilter {
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "any" => Some(NetworkFilter::Any),
+            "residential" => Some(NetworkFilter::Residential),
+            "hosting" => Some(NetworkFilter::Hosting),
+            _ => None,
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            NetworkFilter::Any => "Any",
+            NetworkFilter::Residential => "Residential only",
+            NetworkFilter::Hosting => "Hosting only",
+        }
+    }
+
+    /// The next filter in the config menu's cycle
+    pub fn next(&self) -> Self {
+        match self {
+            NetworkFilter::Any => NetworkFilter::Residential,
+            NetworkFilter::Residential => NetworkFilter::Hosting,
+            NetworkFilter::Hosting => NetworkFilter::Any,
+        }
+    }
+
+    /// Whether a host passes; hosts without metadata only pass `Any`
+    pub fn accepts(&self, host: Option<&HostInfo>) -> bool {
+        match (self, host) {
+            (NetworkFilter::Any, _) => true,
+            (_, None) => false,
+            (NetworkFilter::Residential, Some(host)) => !host.is_hosting(),
+            (NetworkFilter::Hosting, Some(host)) => host.is_hosting(),
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use super::{url_host, Candidate, DiscoveryQuery, DiscoveryResult, DiscoverySource};
+use crate::error::Error;
+use futures::future::BoxFuture;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::net::IpAddr;
+
+/// Candidates kept from one imported list; the rest are dropped
+pub const MAX_IMPORT_CANDIDATES: usize = 1000;
+/// Port assumed for lines that only list an address (e.g. zmap output)
+const DEFAULT_IMPORT_PORT: u16 = 8545;
+
+#[derive(Debug, Deserialize)]
+struct MasscanHost {
+    ip: String,
+    #[serde(default)]
+    ports: Vec<MasscanPort>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MasscanPort {
+    port: u16,
+    #[serde(default)]
+    status: Option<String>,
+}
+
+/// Candidates read from a user-supplied list instead of a scan service:
+/// plain `ip[:port]` lines (zmap output, CSV `ip,port`), masscan JSON or
+/// `-oL` list output, and endpoint URLs such as chainlist exports
+#[derive(Debug, Clone, Default)]
+pub struct ImportedList {
+    candidates: Vec<Candidate>,
+    /// Non-empty lines that could not be read as a candidate
+    pub skipped: usize,
+    /// Candidates beyond `MAX_IMPORT_CANDIDATES`
+    pub dropped: usize,
+}
+
+impl ImportedList {
+    pub fn parse(text: &str) -> Self {
+        let mut list = Self::default();
+        let mut seen = HashSet::new();
+
+        let candidates = match parse_masscan_json(text) {
+            Some((candidates, skipped)) => {
+                list.skipped = skipped;
+                candidates
+            }
+            None => {
+                let mut candidates = Vec::new();
+                for line in text.lines() {
+                    match parse_line(line) {
+                        Some(found) => candidates.extend(found),
+                        None => list.skipped += 1,
+                    }
+                }
+                candidates
+            }
+        };
+
+        for candidate in candidates {
+            if !seen.insert((candidate.ip.clone(), candidate.port, candidate.endpoint.clone())) {
+                continue;
+            }
+            if list.candidates.len() >= MAX_IMPORT_CANDIDATES {
+                list.dropped += 1;
+                continue;
+            }
+            list.candidates.push(candidate);
+        }
+
+        list
+    }
+
+    pub fn candidates(&self) -> &[Candidate] {
+        &self.candidates
+    }
+
+    pub fn len(&self) -> usize {
+        self.candidates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.candidates.is_empty()
+    }
+}
+
+impl DiscoverySource for ImportedList {
+    fn name(&self) -> &'static str {
+        "import"
+    }
+
+    /// The whole list, whatever the query; imports carry no location
+    fn search<'a>(&'a self, _query: &'a DiscoveryQuery) -> BoxFuture<'a, Result<DiscoveryResult, Error>> {
+        Box::pin(async move {
+            Ok(DiscoveryResult {
+                candidates: self.candidates.clone(),
+                total: self.candidates.len() as u64,
+                pages_fetched: 0,
+            })
+        })
+    }
+}
+
+/// masscan `-oJ` output: a JSON array of hosts, which older masscan versions
+/// write with a trailing comma, so fall back to one object per line.
+/// Returns the candidates and the number of open ports on unusable addresses,
+/// or `None` when the text is not masscan JSON.
+fn parse_masscan_json(text: &str) -> Option<(Vec<Candidate>, usize)> {
+    let trimmed = text.trim_start();
+    if !trimmed.starts_with('[') && !trimmed.starts_with('{') {
+        return None;
+    }
+
+    let hosts: Vec<MasscanHost> = match serde_json::from_str(trimmed) {
+        Ok(hosts) => hosts,
+        Err(_) => text
+            .lines()
+            .map(|line| line.trim().trim_end_matches(','))
+            .filter(|line| line.starts_with('{'))
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect(),
+    };
+    if hosts.is_empty() {
+        return None;
+    }
+
+    let mut candidates = Vec::new();
+    let mut skipped = 0;
+    for host in hosts {
+        for port in host.ports.iter().filter(|p| p.status.as_deref().is_none_or(|s| s == "open")) {
+            match listed_candidate(&host.ip, port.port) {
+                Some(candidate) => candidates.push(candidate),
+                None => skipped += 1,
+            }
+        }
+    }
+    Some((candidates, skipped))
+}
+
+/// Candidates on one line; `Some(empty)` for blank and comment lines,
+/// `None` for lines that yield no candidate
+fn parse_line(line: &str) -> Option<Vec<Candidate>> {
+    let line = line.split('#').next().unwrap_or("").trim();
+    if line.is_empty() {
+        return Some(Vec::new());
+    }
+
+    // URL lists, including URLs embedded in JSON such as chainlist exports
+    if line.contains("://") {
+        let urls: Vec<Candidate> = line
+            .split(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | ',' | '<' | '>'))
+            .filter_map(url_candidate)
+            .collect();
+        return (!urls.is_empty()).then_some(urls);
+    }
+
+    let tokens: Vec<&str> = line
+        .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    match tokens.as_slice() {
+        // masscan -oL: "open tcp 8545 203.0.113.1 1700000000"
+        ["open", "tcp", port, ip, ..] => listed_candidate(ip, port.parse().ok()?).map(|c| vec![c]),
+        [address] => {
+            let (host, port) = split_host_port(address)?;
+            listed_candidate(host, port.unwrap_or(DEFAULT_IMPORT_PORT)).map(|c| vec![c])
+        }
+        // CSV such as zmap's "saddr,sport"
+        [host, port] => listed_candidate(host, port.parse().ok()?).map(|c| vec![c]),
+        _ => None,
+    }
+}
+
+/// `host`, `host:port`, `[v6]` or `[v6]:port`
+fn split_host_port(address: &str) -> Option<(&str, Option<u16>)> {
+    if let Some(rest) = address.strip_prefix('[') {
+        let (host, after) = rest.split_once(']')?;
+        let port = match after.strip_prefix(':') {
+            Some(port) => Some(port.parse().ok()?),
+            None if after.is_empty() => None,
+            None => return None,
+        };
+        return Some((host, port));
+    }
+
+    // A bare IPv6 address has several colons and no port
+    if address.parse::<IpAddr>().is_ok() {
+        return Some((address, None));
+    }
+    match address.rsplit_once(':') {
+        Some((host, port)) => Some((host, Some(port.parse().ok()?))),
+        None => Some((address, None)),
+    }
+}
+
+/// Candidate for an address and port given explicitly by a list
+fn listed_candidate(host: &str, port: u16) -> Option<Candidate> {
+    let host = normalize_host(host)?;
+    let mut candidate = Candidate::new("import", host, port);
+    candidate.listed = true;
+    Some(candidate)
+}
+
+/// Candidate validated at exactly this URL
+fn url_candidate(token: &str) -> Option<Candidate> {
+    let token = trim_url(token);
+    if token.contains(['$', '{', '}']) {
+        // Templated URLs such as chainlist's "https://mainnet.infura.io/v3/${INFURA_API_KEY}"
+        return None;
+    }
+    let url = url::Url::parse(token).ok()?;
+    if !matches!(url.scheme(), "http" | "https" | "ws" | "wss") {
+        return None;
+    }
+
+    let host = normalize_host(url.host_str()?)?;
+    let mut candidate = Candidate::new("import", host, url.port_or_known_default()?);
+    candidate.endpoint = Some(token.to_string());
+    Some(candidate)
+}
+
+/// Strip brackets and punctuation around a URL. A `]` is kept when it
+/// closes an IPv6 host such as `http://[2001:db8::1]`.
+fn trim_url(token: &str) -> &str {
+    let mut token = token.trim_start_matches(['(', '[']);
+    loop {
+        let trimmed = token.trim_end_matches(['/', '.', ')']);
+        let trimmed = match trimmed.strip_suffix(']') {
+            Some(rest) if trimmed.matches(']').count() > trimmed.matches('[').count() => rest,
+            _ => trimmed,
+        };
+        if trimmed.len() == token.len() {
+            return token;
+        }
+        token = trimmed;
+    }
+}
+
+/// IPs as used in URLs (IPv6 in brackets) or a plausible hostname
+fn normalize_host(host: &str) -> Option<String> {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    match host.parse::<IpAddr>() {
+        Ok(ip) => Some(url_host(ip)),
+        Err(_) => {
+            let valid = host.contains('.')
+                && host
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+            valid.then(|| host.to_lowercase())
+        }
+    }
+}
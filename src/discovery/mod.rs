@@ -0,0 +1,398 @@
+//! Sources of candidate nodes. Each internet-scan service implements
+//! [`DiscoverySource`]; [`Discovery`] queries every configured source
+//! concurrently and merges their candidates by IP and port. Lists the user
+//! already has (masscan output, URL lists) come in through [`import`], and
+//! trusted ranges can be scanned directly with [`scanner`]. Host metadata
+//! (org, ASN, city) and the provider filters on it live in [`hosting`].
+
+pub mod censys;
+pub mod fofa;
+pub mod hosting;
+pub mod import;
+pub mod scanner;
+pub mod zoomeye;
+
+use crate::error::Error;
+use crate::shodan::{ShodanClient, ShodanQuery};
+use futures::future::{join_all, BoxFuture};
+use hosting::HostInfo;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Hostnames tried per TLS candidate before falling back to the bare IP
+const MAX_TLS_HOSTNAMES: usize = 3;
+
+/// Ports searched on every source; 443 catches nodes behind TLS-terminating proxies
+pub const RPC_PORTS: [u16; 3] = [8545, 8546, 443];
+
+/// An IP as written in URLs, IPv6 in brackets
+pub fn url_host(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(ip) => ip.to_string(),
+        IpAddr::V6(ip) => format!("[{}]", ip),
+    }
+}
+
+/// A host and port some source reported as a possible RPC node
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    /// The IP as written in URLs, IPv6 in brackets
+    pub ip: String,
+    pub port: u16,
+    pub country_code: Option<String>,
+    /// Hostnames the sources associate with the IP, plus the certificate's
+    /// common name when it is not a wildcard
+    pub hostnames: Vec<String>,
+    /// Certificate seen on the port, `None` when the service is plain text
+    pub certificate: Option<CertificateInfo>,
+    /// Names of the sources that reported this candidate
+    pub sources: Vec<&'static str>,
+    /// Exact URL to validate instead of URLs built from the IP and port,
+    /// for imported endpoint lists
+    pub endpoint: Option<String>,
+    /// The port was chosen by the user (an imported list, a scan, extra
+    /// Shodan ports), so it is validated even when it is not one of `RPC_PORTS`
+    pub listed: bool,
+    /// Org, ASN, location and scan details, `None` when the source has none
+    pub host: Option<HostInfo>,
+    /// The source's query matched the chain ID (Shodan's "Chain Id" banner).
+    /// Other candidates get an `eth_chainId` pre-check before validation.
+    pub chain_matched: bool,
+}
+
+/// TLS certificate details from a source's scan
+#[derive(Debug, Clone, Default)]
+pub struct CertificateInfo {
+    /// Unix time the certificate expires
+    pub expires_at: Option<u64>,
+    /// Issuer organization, or common name when it has none
+    pub issuer: Option<String>,
+}
+
+impl Candidate {
+    /// Search sources report IPv6 unbracketed, imports and scans bracket it;
+    /// both are stored bracketed and in canonical form so the same host
+    /// merges across sources
+    pub fn new(source: &'static str, ip: String, port: u16) -> Self {
+        let ip = match ip.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+            Ok(addr) => url_host(addr),
+            Err(_) => ip,
+        };
+        Self {
+            ip,
+            port,
+            country_code: None,
+            hostnames: Vec::new(),
+            certificate: None,
+            sources: vec![source],
+            endpoint: None,
+            listed: false,
+            host: None,
+            chain_matched: false,
+        }
+    }
+
+    /// Add hostnames, skipping wildcards, the IP itself and duplicates
+    pub fn add_hostnames<I: IntoIterator<Item = String>>(&mut self, hostnames: I) {
+        for host in hostnames {
+            let host = host.trim().to_lowercase();
+            if !host.is_empty() && !host.starts_with("*.") && host != self.ip && !self.hostnames.contains(&host) {
+                self.hostnames.push(host);
+            }
+        }
+    }
+
+    pub fn http_url(&self) -> String {
+        format!("http://{}:{}", self.ip, self.port)
+    }
+
+    /// HTTPS URLs to try for a TLS service: each hostname (a certificate
+    /// only validates for the name it was issued to), then the bare IP.
+    /// Empty when no source saw TLS on the port.
+    pub fn https_urls(&self) -> Vec<String> {
+        if !self.is_tls() {
+            return Vec::new();
+        }
+
+        let authority = |host: &str| match self.port {
+            443 => host.to_string(),
+            port => format!("{}:{}", host, port),
+        };
+        self.hostnames
+            .iter()
+            .take(MAX_TLS_HOSTNAMES)
+            .map(|host| format!("https://{}", authority(host)))
+            .chain(std::iter::once(format!("https://{}", authority(&self.ip))))
+            .collect()
+    }
+
+    pub fn is_tls(&self) -> bool {
+        self.certificate.is_some()
+    }
+
+    /// WebSocket URLs to try, most likely first: the port the source found
+    /// (many clients and proxies serve WS on the HTTP port), each of `ports`,
+    /// then `wss://` on 443 by hostname and by IP, each combined with every path
+    pub fn ws_urls(&self, ports: &[u16], paths: &[String]) -> Vec<String> {
+        let mut endpoints: Vec<(&str, u16)> = vec![("ws", self.port)];
+        for &port in ports {
+            if !endpoints.contains(&("ws", port)) {
+                endpoints.push(("ws", port));
+            }
+        }
+
+        let default_path = [String::new()];
+        let paths = if paths.is_empty() { &default_path[..] } else { paths };
+
+        let ip = &self.ip;
+        let plain = endpoints.into_iter().flat_map(|(scheme, port)| {
+            paths
+                .iter()
+                .map(move |path| format!("{}://{}:{}{}", scheme, ip, port, path))
+        });
+        let hosts = self.hostnames.iter().take(MAX_TLS_HOSTNAMES).chain(std::iter::once(ip));
+        let tls = hosts.flat_map(|host| paths.iter().map(move |path| format!("wss://{}{}", host, path)));
+        plain.chain(tls).collect()
+    }
+
+    /// Host metadata with every hostname the sources reported, as kept on
+    /// validated nodes
+    pub fn host_info(&self) -> Option<HostInfo> {
+        let mut host = self.host.clone()?;
+        host.hostnames = self.hostnames.clone();
+        Some(host)
+    }
+
+    pub fn is_http_port(&self) -> bool {
+        self.port == 8545
+    }
+
+    pub fn is_ws_port(&self) -> bool {
+        self.port == 8546
+    }
+
+    /// Fold in what another source knows about the same IP and port
+    fn merge(&mut self, other: Candidate) {
+        if self.country_code.is_none() {
+            self.country_code = other.country_code;
+        }
+        self.add_hostnames(other.hostnames);
+        if self.certificate.is_none() {
+            self.certificate = other.certificate;
+        }
+        if self.host.is_none() {
+            self.host = other.host;
+        }
+        self.chain_matched |= other.chain_matched;
+        for source in other.sources {
+            if !self.sources.contains(&source) {
+                self.sources.push(source);
+            }
+        }
+    }
+}
+
+/// What to look for on every source
+#[derive(Debug, Clone)]
+pub struct DiscoveryQuery {
+    pub chain_id: u64,
+    pub country_code: Option<String>,
+    /// Stop paging once this many candidates are collected
+    pub min_results: usize,
+    /// Maximum result pages (query credits) spent on each source
+    pub max_pages: u32,
+    /// Extra ports, products, networks or a raw query for Shodan
+    pub shodan: ShodanQuery,
+}
+
+/// Candidates collected across one or more result pages of one source
+#[derive(Debug, Clone, Default)]
+pub struct DiscoveryResult {
+    pub candidates: Vec<Candidate>,
+    /// Total number of matches the source reports for the query
+    pub total: u64,
+    pub pages_fetched: u32,
+}
+
+/// An internet-scan service that can list possible RPC nodes
+pub trait DiscoverySource: Send + Sync {
+    /// Short lowercase name used in logs and diagnostics
+    fn name(&self) -> &'static str;
+
+    fn search<'a>(&'a self, query: &'a DiscoveryQuery) -> BoxFuture<'a, Result<DiscoveryResult, Error>>;
+}
+
+/// Spaces out requests to one API
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    interval: Duration,
+    last_request: Arc<Mutex<Option<Instant>>>,
+}
+
+impl RateLimiter {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_request: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Wait until `interval` has passed since the previous request
+    pub async fn wait(&self) {
+        let mut last = self.last_request.lock().await;
+        if let Some(last_time) = *last {
+            let elapsed = last_time.elapsed();
+            if elapsed < self.interval {
+                tokio::time::sleep(self.interval - elapsed).await;
+            }
+        }
+        *last = Some(Instant::now());
+    }
+}
+
+/// How one source did in a search
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SourceReport {
+    pub candidates: usize,
+    /// Total matches the source reports for the query
+    pub total: u64,
+    pub pages: u32,
+    /// Why the source failed, if it did
+    pub error: Option<String>,
+}
+
+/// Candidates from every source, deduplicated by IP, port and endpoint URL
+#[derive(Debug, Clone, Default)]
+pub struct MergedDiscovery {
+    /// In source order, so earlier sources' candidates are validated first
+    pub candidates: Vec<Candidate>,
+    pub sources: BTreeMap<&'static str, SourceReport>,
+    /// Candidates already reported by an earlier source
+    pub duplicates: usize,
+}
+
+/// Every configured discovery source
+#[derive(Clone, Default)]
+pub struct Discovery {
+    sources: Vec<Arc<dyn DiscoverySource>>,
+}
+
+impl Discovery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_source<S: DiscoverySource + 'static>(mut self, source: S) -> Self {
+        self.sources.push(Arc::new(source));
+        self
+    }
+
+    /// Sources whose credentials are set: `SHODAN_TOKEN`,
+    /// `CENSYS_API_ID` + `CENSYS_API_SECRET`, `FOFA_KEY` (plus `FOFA_EMAIL`
+    /// for older accounts) and `ZOOMEYE_API_KEY`
+    pub fn from_env() -> Self {
+        Self::from_env_with_shodan(ShodanClient::from_env())
+    }
+
+    /// Like [`Self::from_env`], with a Shodan client the caller also uses
+    /// directly (e.g. for facet counts), so both share its rate limit
+    pub fn from_env_with_shodan(shodan: Option<ShodanClient>) -> Self {
+        let var = |name: &str| env::var(name).ok().filter(|v| !v.is_empty());
+
+        let mut discovery = Self::new();
+        if let Some(shodan) = shodan {
+            discovery = discovery.with_source(shodan);
+        }
+        if let (Some(id), Some(secret)) = (var("CENSYS_API_ID"), var("CENSYS_API_SECRET")) {
+            discovery = discovery.with_source(censys::CensysClient::new(id, secret));
+        }
+        if let Some(key) = var("FOFA_KEY") {
+            discovery = discovery.with_source(fofa::FofaClient::new(var("FOFA_EMAIL"), key));
+        }
+        if let Some(key) = var("ZOOMEYE_API_KEY") {
+            discovery = discovery.with_source(zoomeye::ZoomEyeClient::new(key));
+        }
+        discovery
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    pub fn source_names(&self) -> Vec<&'static str> {
+        self.sources.iter().map(|s| s.name()).collect()
+    }
+
+    /// Query all sources concurrently and merge their candidates. Fails only
+    /// when every source fails, with the first source's error.
+    pub async fn search(&self, query: &DiscoveryQuery) -> Result<MergedDiscovery, Error> {
+        if self.sources.is_empty() {
+            return Err(Error::Discovery {
+                provider: "discovery",
+                message: "no discovery source configured".to_string(),
+            });
+        }
+
+        let searches = self.sources.iter().map(|source| source.search(query));
+        let results = join_all(searches).await;
+
+        let mut merged = MergedDiscovery::default();
+        let mut index: HashMap<(String, u16, Option<String>), usize> = HashMap::new();
+        let mut first_error = None;
+        let mut any_succeeded = false;
+
+        for (source, result) in self.sources.iter().zip(results) {
+            let name = source.name();
+            let result = match result {
+                Ok(result) => result,
+                Err(e) => {
+                    log::warn!("Discovery source {} failed: {}", name, e);
+                    merged.sources.insert(
+                        name,
+                        SourceReport {
+                            error: Some(e.to_string()),
+                            ..Default::default()
+                        },
+                    );
+                    first_error.get_or_insert(e);
+                    continue;
+                }
+            };
+
+            any_succeeded = true;
+            merged.sources.insert(
+                name,
+                SourceReport {
+                    candidates: result.candidates.len(),
+                    total: result.total,
+                    pages: result.pages_fetched,
+                    error: None,
+                },
+            );
+
+            for candidate in result.candidates {
+                let key = (candidate.ip.clone(), candidate.port, candidate.endpoint.clone());
+                match index.get(&key) {
+                    Some(&i) => {
+                        merged.candidates[i].merge(candidate);
+                        merged.duplicates += 1;
+                    }
+                    None => {
+                        index.insert(key, merged.candidates.len());
+                        merged.candidates.push(candidate);
+                    }
+                }
+            }
+        }
+
+        match first_error {
+            Some(e) if !any_succeeded => Err(e),
+            _ => Ok(merged),
+        }
+    }
+}
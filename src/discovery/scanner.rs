@@ -0,0 +1,318 @@
+use super::{url_host, Candidate, CertificateInfo, DiscoveryQuery, DiscoveryResult, DiscoverySource, RateLimiter};
+use crate::error::Error;
+use crate::validator::session::WsSession;
+use crate::validator::{RpcRequest, RpcResponse};
+use futures::future::BoxFuture;
+use futures::stream::{self, StreamExt};
+use reqwest::Client;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::net::TcpStream;
+
+/// Ports probed when none are given: Geth-style HTTP and WS, Nethermind/
+/// Erigon/L2 alternatives, Avalanche's 9650 and TLS proxies
+pub const DEFAULT_SCAN_PORTS: [u16; 5] = [8545, 8546, 8547, 9650, 443];
+/// Addresses one scan may cover across all ranges, a /16
+pub const MAX_SCAN_ADDRESSES: u128 = 65_536;
+const DEFAULT_CONCURRENCY: usize = 200;
+const DEFAULT_CONNECTS_PER_SECOND: u32 = 500;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+/// Time an open port gets to answer `eth_chainId`
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// An IPv4 or IPv6 network such as `203.0.113.0/24`; a bare address is a
+/// single host
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cidr {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl FromStr for Cidr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (address, prefix) = s.split_once('/').unwrap_or((s, ""));
+        let address: IpAddr = address.parse().map_err(|_| format!("invalid address '{}'", s))?;
+        let bits = if address.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            "" => bits,
+            prefix => prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|p| *p <= bits)
+                .ok_or_else(|| format!("invalid prefix length in '{}'", s))?,
+        };
+
+        let cidr = Self { network: address, prefix };
+        let network = cidr.first();
+        Ok(Self {
+            network: cidr.address(network),
+            prefix,
+        })
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix)
+    }
+}
+
+impl Cidr {
+    fn bits(&self) -> u8 {
+        if self.network.is_ipv4() {
+            32
+        } else {
+            128
+        }
+    }
+
+    fn host_mask(&self) -> u128 {
+        u128::MAX.checked_shr(u32::from(self.prefix) + 128 - u32::from(self.bits())).unwrap_or(0)
+    }
+
+    /// Lowest address of the network as a number
+    fn first(&self) -> u128 {
+        let value = match self.network {
+            IpAddr::V4(ip) => u128::from(u32::from(ip)),
+            IpAddr::V6(ip) => u128::from(ip),
+        };
+        value & !self.host_mask()
+    }
+
+    fn address(&self, value: u128) -> IpAddr {
+        match self.network {
+            IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::from(value as u32)),
+            IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::from(value)),
+        }
+    }
+
+    /// IPv4 networks larger than a /31 have unusable network and broadcast addresses
+    fn skips_edges(&self) -> bool {
+        self.network.is_ipv4() && self.prefix <= 30
+    }
+
+    /// Number of addresses `addresses` yields, saturating for huge IPv6 networks
+    pub fn len(&self) -> u128 {
+        let size = self.host_mask().saturating_add(1);
+        if self.skips_edges() {
+            size - 2
+        } else {
+            size
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Host addresses in the network, in order
+    pub fn addresses(&self) -> impl Iterator<Item = IpAddr> + '_ {
+        let first = self.first();
+        let last = first | self.host_mask();
+        let (first, last) = if self.skips_edges() { (first + 1, last - 1) } else { (first, last) };
+        (first..=last).map(move |value| self.address(value))
+    }
+}
+
+/// Result of probing one address and port
+enum Probe {
+    Closed,
+    /// Accepted the connection but did not answer `eth_chainId`
+    Open,
+    Answered { chain_id: u64, tls: bool },
+}
+
+/// What a scan found, beyond the candidates it returns
+#[derive(Debug, Clone, Default)]
+pub struct ScanSummary {
+    /// Address and port pairs tried
+    pub probed: usize,
+    /// Pairs accepting a TCP connection
+    pub open: usize,
+    /// Open ports answering `eth_chainId` over HTTP, HTTPS or WebSocket
+    pub answered: usize,
+    /// Answered with another chain's ID
+    pub other_chain: usize,
+}
+
+/// Scans trusted address ranges (own hosting, partner ASNs) directly: a TCP
+/// connect on each port, then `eth_chainId` over HTTP(S) and WebSocket on
+/// the ones that accept. Nodes of the searched chain become candidates.
+pub struct PortScanner {
+    ranges: Vec<Cidr>,
+    ports: Vec<u16>,
+    concurrency: usize,
+    rate_limiter: RateLimiter,
+    client: Client,
+}
+
+impl PortScanner {
+    pub fn new(ranges: Vec<Cidr>) -> Self {
+        Self {
+            ranges,
+            ports: DEFAULT_SCAN_PORTS.to_vec(),
+            concurrency: DEFAULT_CONCURRENCY,
+            rate_limiter: RateLimiter::new(Duration::from_secs(1) / DEFAULT_CONNECTS_PER_SECOND),
+            client: Client::builder()
+                .timeout(PROBE_TIMEOUT)
+                .danger_accept_invalid_certs(true)
+                .build()
+                .expect("Failed to create HTTP client"),
+        }
+    }
+
+    pub fn with_ports(mut self, ports: Vec<u16>) -> Self {
+        self.ports = ports;
+        self
+    }
+
+    /// Probes in flight at once
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// TCP connections started per second
+    pub fn with_rate(mut self, connects_per_second: u32) -> Self {
+        self.rate_limiter = RateLimiter::new(Duration::from_secs(1) / connects_per_second.max(1));
+        self
+    }
+
+    /// Addresses across all ranges
+    pub fn address_count(&self) -> u128 {
+        self.ranges.iter().fold(0u128, |sum, r| sum.saturating_add(r.len()))
+    }
+
+    /// Address and port pairs a scan tries
+    pub fn target_count(&self) -> u128 {
+        self.address_count().saturating_mul(self.ports.len() as u128)
+    }
+
+    /// Probe every address and port; candidates are the nodes answering
+    /// with `chain_id`
+    pub async fn scan(&self, chain_id: u64) -> Result<(Vec<Candidate>, ScanSummary), Error> {
+        let addresses = self.address_count();
+        if addresses > MAX_SCAN_ADDRESSES {
+            return Err(Error::Discovery {
+                provider: "scan",
+                message: format!("{} addresses exceed the limit of {}", addresses, MAX_SCAN_ADDRESSES),
+            });
+        }
+
+        let mut targets = Vec::new();
+        for ip in self.ranges.iter().flat_map(Cidr::addresses) {
+            targets.extend(self.ports.iter().map(|&port| (ip, port)));
+        }
+
+        let probes: Vec<(IpAddr, u16, Probe)> =
+            stream::iter(targets.into_iter().map(|(ip, port)| self.probe(ip, port)))
+                .buffer_unordered(self.concurrency)
+                .collect()
+                .await;
+
+        let mut summary = ScanSummary {
+            probed: probes.len(),
+            ..Default::default()
+        };
+        let mut candidates = Vec::new();
+        for (ip, port, probe) in probes {
+            match probe {
+                Probe::Closed => continue,
+                Probe::Open => summary.open += 1,
+                Probe::Answered { chain_id: id, tls } => {
+                    summary.open += 1;
+                    summary.answered += 1;
+                    if id != chain_id {
+                        summary.other_chain += 1;
+                        continue;
+                    }
+
+                    let mut candidate = Candidate::new("scan", url_host(ip), port);
+                    candidate.listed = true;
+                    if tls {
+                        candidate.certificate = Some(CertificateInfo::default());
+                    }
+                    candidates.push(candidate);
+                }
+            }
+        }
+
+        // Probes finish out of order; list hits by address and port
+        candidates.sort_by(|a, b| (&a.ip, a.port).cmp(&(&b.ip, b.port)));
+        log::info!("Scan of {} ranges for chain {}: {:?}", self.ranges.len(), chain_id, summary);
+        Ok((candidates, summary))
+    }
+
+    async fn probe(&self, ip: IpAddr, port: u16) -> (IpAddr, u16, Probe) {
+        (ip, port, self.probe_port(ip, port).await)
+    }
+
+    async fn probe_port(&self, ip: IpAddr, port: u16) -> Probe {
+        self.rate_limiter.wait().await;
+        let connect = tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(SocketAddr::new(ip, port))).await;
+        if !matches!(connect, Ok(Ok(_))) {
+            return Probe::Closed;
+        }
+
+        let host = url_host(ip);
+        let request = RpcRequest::eth_chain_id();
+
+        // TLS proxies on 443 first over HTTPS, everything else over plain HTTP
+        let (scheme, tls) = if port == 443 { ("https", true) } else { ("http", false) };
+        let url = format!("{}://{}:{}", scheme, host, port);
+        if let Ok(chain_id) = self.http_chain_id(&url, &request).await {
+            return Probe::Answered { chain_id, tls };
+        }
+
+        // WebSocket-only ports reject plain HTTP requests
+        if !tls {
+            if let Ok(chain_id) = ws_chain_id(&format!("ws://{}:{}", host, port), &request).await {
+                return Probe::Answered { chain_id, tls };
+            }
+        }
+
+        Probe::Open
+    }
+
+    async fn http_chain_id(&self, url: &str, request: &RpcRequest) -> Result<u64, Error> {
+        let response = self.client.post(url).json(request).send().await?;
+        if !response.status().is_success() {
+            return Err(Error::HttpStatus(response.status().as_u16()));
+        }
+        response.json::<RpcResponse>().await?.into_u64()
+    }
+}
+
+async fn ws_chain_id(url: &str, request: &RpcRequest) -> Result<u64, Error> {
+    let probe = async {
+        let mut session = WsSession::connect(url).await?;
+        let response = session.call(request).await;
+        session.close().await;
+        response?.into_u64()
+    };
+    tokio::time::timeout(PROBE_TIMEOUT, probe).await.map_err(|_| Error::Timeout)?
+}
+
+impl DiscoverySource for PortScanner {
+    fn name(&self) -> &'static str {
+        "scan"
+    }
+
+    /// Every hit of the scan; ranges are chosen by the user, so the country
+    /// filter does not apply
+    fn search<'a>(&'a self, query: &'a DiscoveryQuery) -> BoxFuture<'a, Result<DiscoveryResult, Error>> {
+        Box::pin(async move {
+            let (candidates, summary) = self.scan(query.chain_id).await?;
+            Ok(DiscoveryResult {
+                candidates,
+                total: summary.open as u64,
+                pages_fetched: 0,
+            })
+        })
+    }
+}
@@ -0,0 +1,164 @@
+use super::{Candidate, CertificateInfo, DiscoveryQuery, DiscoveryResult, DiscoverySource, RateLimiter, RPC_PORTS};
+use crate::error::Error;
+use futures::future::BoxFuture;
+use reqwest::Client;
+use serde::Deserialize;
+use std::time::Duration;
+
+const ZOOMEYE_API_BASE: &str = "https://api.zoomeye.org";
+const RATE_LIMIT_INTERVAL: Duration = Duration::from_secs(1);
+/// ZoomEye returns at most 20 matches per page of `/host/search`
+const ZOOMEYE_PAGE_SIZE: usize = 20;
+
+#[derive(Debug, Deserialize)]
+struct ZoomEyeResponse {
+    #[serde(default)]
+    total: u64,
+    #[serde(default)]
+    matches: Vec<ZoomEyeMatch>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ZoomEyeMatch {
+    ip: String,
+    portinfo: ZoomEyePortInfo,
+    geoinfo: Option<ZoomEyeGeoInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ZoomEyePortInfo {
+    port: u16,
+    #[serde(default)]
+    service: String,
+    #[serde(default)]
+    hostname: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ZoomEyeGeoInfo {
+    country: Option<ZoomEyeCountry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ZoomEyeCountry {
+    code: Option<String>,
+}
+
+impl ZoomEyeMatch {
+    fn into_candidate(self) -> Candidate {
+        let mut candidate = Candidate::new("zoomeye", self.ip, self.portinfo.port);
+        candidate.country_code = self.geoinfo.and_then(|g| g.country).and_then(|c| c.code);
+        candidate.add_hostnames(Some(self.portinfo.hostname));
+        if self.portinfo.service == "https" {
+            candidate.certificate = Some(CertificateInfo::default());
+        }
+        candidate
+    }
+}
+
+/// ZoomEye host search API, authenticated with an API key header
+#[derive(Debug, Clone)]
+pub struct ZoomEyeClient {
+    client: Client,
+    api_key: String,
+    base_url: String,
+    rate_limiter: RateLimiter,
+}
+
+impl ZoomEyeClient {
+    pub fn new(api_key: String) -> Self {
+        Self {
+            client: Client::new(),
+            api_key,
+            base_url: ZOOMEYE_API_BASE.to_string(),
+            rate_limiter: RateLimiter::new(RATE_LIMIT_INTERVAL),
+        }
+    }
+
+    /// Send requests to another API root, e.g. a proxy or a test server
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    fn error(message: String) -> Error {
+        Error::Discovery {
+            provider: "zoomeye",
+            message,
+        }
+    }
+
+    /// ZoomEye does not index the chain ID, so this matches JSON-RPC servers
+    /// of every chain; the search pre-checks their chain ID before validation
+    fn build_query(country_code: Option<&str>) -> String {
+        let ports: Vec<String> = RPC_PORTS.iter().map(|p| format!("port:{}", p)).collect();
+        let mut query = format!("+\"jsonrpc\" +({})", ports.join(" "));
+
+        if let Some(cc) = country_code {
+            query.push_str(&format!(" +country:\"{}\"", cc));
+        }
+
+        query
+    }
+
+    pub async fn search_nodes(&self, query: &DiscoveryQuery) -> Result<DiscoveryResult, Error> {
+        let zoomeye_query = Self::build_query(query.country_code.as_deref());
+
+        let mut search = DiscoveryResult::default();
+
+        while search.pages_fetched < query.max_pages.max(1) {
+            let page = search.pages_fetched + 1;
+            let data = self.fetch_page(&zoomeye_query, page).await?;
+            search.pages_fetched = page;
+            search.total = data.total;
+
+            let page_len = data.matches.len();
+            search.candidates.extend(
+                data.matches
+                    .into_iter()
+                    .filter(|m| RPC_PORTS.contains(&m.portinfo.port))
+                    .map(ZoomEyeMatch::into_candidate),
+            );
+
+            let exhausted = page_len < ZOOMEYE_PAGE_SIZE || (page as u64 * ZOOMEYE_PAGE_SIZE as u64) >= search.total;
+            if exhausted || search.candidates.len() >= query.min_results {
+                break;
+            }
+        }
+
+        Ok(search)
+    }
+
+    async fn fetch_page(&self, query: &str, page: u32) -> Result<ZoomEyeResponse, Error> {
+        self.rate_limiter.wait().await;
+
+        let response = self
+            .client
+            .get(format!("{}/host/search", self.base_url))
+            .query(&[("query", query.to_string()), ("page", page.to_string())])
+            .header("API-KEY", &self.api_key)
+            .timeout(Duration::from_secs(30))
+            .send()
+            .await
+            .map_err(|e| Self::error(format!("request failed: {}", e.without_url())))?;
+
+        if !response.status().is_success() {
+            return Err(Self::error(format!("API error: {}", response.status())));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| Self::error(format!("failed to parse response: {}", e.without_url())))
+    }
+}
+
+impl DiscoverySource for ZoomEyeClient {
+    fn name(&self) -> &'static str {
+        "zoomeye"
+    }
+
+    fn search<'a>(&'a self, query: &'a DiscoveryQuery) -> BoxFuture<'a, Result<DiscoveryResult, Error>> {
+        Box::pin(self.search_nodes(query))
+    }
+}
@@ -0,0 +1,85 @@
+use thiserror::Error as ThisError;
+
+/// Why a request to a discovery source or a node failed, or why a node was rejected
+#[derive(Debug, Clone, PartialEq, ThisError)]
+pub enum Error {
+    #[error("Request timed out")]
+    Timeout,
+
+    #[error("Connection failed: {0}")]
+    Connect(String),
+
+    #[error("HTTP status: {0}")]
+    HttpStatus(u16),
+
+    #[error("RPC error {code}: {message}")]
+    RpcError { code: i64, message: String },
+
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+
+    #[error("WebSocket error: {0}")]
+    WebSocket(String),
+
+    #[error("Chain ID mismatch: expected {expected}, got {actual}")]
+    ChainIdMismatch { expected: u64, actual: u64 },
+
+    #[error("Genesis hash mismatch - possible honeypot")]
+    GenesisMismatch,
+
+    #[error("No genesis hash pinned for chain {chain_id} - fetch and confirm block 0 first")]
+    GenesisUnpinned { chain_id: u64 },
+
+    #[error("Block hash mismatch at block {block} - node is on a fork or faking the chain")]
+    ForkMismatch { block: u64 },
+
+    #[error("Node not synced: {behind} blocks behind (tolerance: {tolerance})")]
+    NotSynced { behind: u64, tolerance: u64 },
+
+    #[error("No archive state at block {block}")]
+    ArchiveMissing { block: u64 },
+
+    #[error("{provider} search failed: {message}")]
+    Discovery { provider: &'static str, message: String },
+
+    #[error("Reference node unavailable: {0}. Configure a custom RPC in settings or try again later.")]
+    ReferenceUnavailable(Box<Error>),
+}
+
+impl Error {
+    /// Stable short name of the variant, for counting rejection reasons
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Error::Timeout => "timeout",
+            Error::Connect(_) => "connect",
+            Error::HttpStatus(_) => "http_status",
+            Error::RpcError { .. } => "rpc_error",
+            Error::InvalidResponse(_) => "invalid_response",
+            Error::WebSocket(_) => "websocket",
+            Error::ChainIdMismatch { .. } => "chain_id_mismatch",
+            Error::GenesisMismatch => "genesis_mismatch",
+            Error::GenesisUnpinned { .. } => "genesis_unpinned",
+            Error::NotSynced { .. } => "not_synced",
+            Error::ForkMismatch { .. } => "fork",
+            Error::ArchiveMissing { .. } => "archive_missing",
+            Error::Discovery { .. } => "discovery",
+            Error::ReferenceUnavailable(_) => "reference_unavailable",
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            Error::Timeout
+        } else if e.is_connect() {
+            Error::Connect(e.to_string())
+        } else if let Some(status) = e.status() {
+            Error::HttpStatus(status.as_u16())
+        } else if e.is_decode() {
+            Error::InvalidResponse(e.to_string())
+        } else {
+            Error::Connect(e.to_string())
+        }
+    }
+}
@@ -0,0 +1,42 @@
+//! Discovery and validation of public EVM JSON-RPC nodes.
+//!
+//! Candidates come from Shodan, Censys, FOFA and ZoomEye, are checked against
+//! a chain's ID, genesis hash and a reference block height, and are optionally
+//! probed for archive state, trace/debug capabilities and dangerous exposure. The Telegram bot and the
+//! CLI in this package are both thin consumers of [`SearchPipeline`].
+//!
+//! ```no_run
+//! use node_finder::discovery::Discovery;
+//! use node_finder::shodan::ShodanClient;
+//! use node_finder::{chains, NodeType, SearchPipeline, SearchRequest};
+//!
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let discovery = Discovery::new().with_source(ShodanClient::new("SHODAN_API_KEY".to_string()));
+//! let pipeline = SearchPipeline::new(discovery);
+//! let chain = chains::get_chain_by_id(1).ok_or("unknown chain")?;
+//!
+//! let mut request = SearchRequest::new(chain, NodeType::Archive);
+//! request.country_code = Some("DE".to_string());
+//! request.config.default_count = 5;
+//!
+//! let outcome = pipeline.search(&request).await?;
+//! for node in &outcome.nodes {
+//!     println!("{} ({} ms)", node.url, node.latency_ms);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod chains;
+pub mod config;
+pub mod discovery;
+pub mod error;
+pub mod revalidation;
+pub mod search;
+pub mod shodan;
+pub mod store;
+pub mod validator;
+
+pub use error::Error;
+pub use search::{NodeType, SearchDiagnostics, SearchOutcome, SearchPipeline, SearchRequest};
+pub use validator::ValidatedNode;
@@ -1,11 +1,11 @@
 mod bot;
-mod chains;
-mod config;
-mod shodan;
-mod validator;
+mod cli;
 
-use bot::{callbacks, commands, BotState};
+use bot::{callbacks, commands, monitor, BotState};
 use dotenvy::dotenv;
+use node_finder::discovery::Discovery;
+use node_finder::revalidation::{RevalidationConfig, Revalidator};
+use node_finder::shodan::ShodanClient;
 use std::env;
 use teloxide::prelude::*;
 
@@ -15,15 +15,34 @@ async fn main() {
     dotenv().ok();
     env_logger::init();
 
+    // Any arguments select the CLI; no arguments run the bot
+    let args: Vec<String> = env::args().skip(1).collect();
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args).await);
+    }
+
     log::info!("Starting Node Finder bot...");
 
     let telegram_token = env::var("TELEGRAM_TOKEN")
         .expect("TELEGRAM_TOKEN must be set in .env file");
-    let shodan_token = env::var("SHODAN_TOKEN")
-        .expect("SHODAN_TOKEN must be set in .env file");
+    let shodan = ShodanClient::from_env();
+    let discovery = Discovery::from_env_with_shodan(shodan.clone());
+    if discovery.is_empty() {
+        panic!("{}", cli::NO_SOURCE_CONFIGURED);
+    }
+    log::info!("Discovery sources: {}", discovery.source_names().join(", "));
 
     let bot = Bot::new(telegram_token);
-    let state = BotState::new(shodan_token);
+    let state = BotState::new(discovery, shodan);
+
+    // Keep the node database fresh in the background while the bot runs
+    if let Some(store) = state.pipeline.store.clone() {
+        let revalidator = Revalidator::new(state.pipeline.clone(), store, RevalidationConfig::from_env());
+        tokio::spawn(revalidator.run());
+    }
+
+    // Alert users about their watched nodes
+    tokio::spawn(monitor::run(bot.clone(), state.clone()));
 
     let handler = dptree::entry()
         .branch(
@@ -36,6 +55,11 @@ async fn main() {
                 .filter(|msg: Message| msg.text().is_some())
                 .endpoint(handle_message_with_state),
         )
+        .branch(
+            Update::filter_message()
+                .filter(|msg: Message| msg.document().is_some())
+                .endpoint(handle_document_with_state),
+        )
         .branch(
             Update::filter_callback_query()
                 .endpoint(handle_callback_with_state),
@@ -66,6 +90,14 @@ async fn handle_message_with_state(
     commands::handle_message(bot, msg, state).await
 }
 
+async fn handle_document_with_state(
+    bot: Bot,
+    msg: Message,
+    state: BotState,
+) -> ResponseResult<()> {
+    commands::handle_document(bot, msg, state).await
+}
+
 async fn handle_callback_with_state(
     bot: Bot,
     q: CallbackQuery,
@@ -0,0 +1,212 @@
+use crate::chains::get_chain_by_id;
+use crate::config::UserConfig;
+use crate::error::Error;
+use crate::search::SearchPipeline;
+use crate::store::{NodeRecord, NodeStore};
+use crate::validator::ValidatedNode;
+use futures::stream::{self, StreamExt};
+use std::collections::HashMap;
+use std::env;
+use std::time::Duration;
+
+/// How often and how hard known nodes are re-checked, read from the
+/// `REVALIDATE_*` environment variables
+#[derive(Debug, Clone)]
+pub struct RevalidationConfig {
+    /// Time between passes; zero disables re-validation
+    pub interval: Duration,
+    /// Nodes validated at the same time
+    pub concurrency: usize,
+    /// Consecutive failed checks after which a node is evicted
+    pub max_failures: u32,
+    pub sync_tolerance: u64,
+}
+
+impl Default for RevalidationConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(30 * 60),
+            concurrency: 20,
+            max_failures: 3,
+            sync_tolerance: UserConfig::default().sync_tolerance,
+        }
+    }
+}
+
+impl RevalidationConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            interval: env_number("REVALIDATE_INTERVAL_SECS")
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.interval),
+            concurrency: env_number("REVALIDATE_CONCURRENCY")
+                .map(|n| (n as usize).max(1))
+                .unwrap_or(defaults.concurrency),
+            max_failures: env_number("REVALIDATE_MAX_FAILURES")
+                .map(|n| (n as u32).max(1))
+                .unwrap_or(defaults.max_failures),
+            sync_tolerance: env_number("REVALIDATE_SYNC_TOLERANCE").unwrap_or(defaults.sync_tolerance),
+        }
+    }
+}
+
+fn env_number(name: &str) -> Option<u64> {
+    let value = env::var(name).ok().filter(|v| !v.is_empty())?;
+    match value.parse() {
+        Ok(n) => Some(n),
+        Err(_) => {
+            log::error!("{} must be a number, got '{}'", name, value);
+            None
+        }
+    }
+}
+
+/// Counts from one re-validation pass
+#[derive(Debug, Clone, Default)]
+pub struct RevalidationSummary {
+    pub checked: usize,
+    pub healthy: usize,
+    pub failed: usize,
+    pub evicted: usize,
+    /// Nodes whose chain has no genesis hash in the registry or their record,
+    /// or whose reference RPCs are unreachable
+    pub skipped: usize,
+}
+
+/// Periodically re-runs validation on the nodes in the node database
+pub struct Revalidator {
+    pipeline: SearchPipeline,
+    store: NodeStore,
+    config: RevalidationConfig,
+}
+
+impl Revalidator {
+    pub fn new(pipeline: SearchPipeline, store: NodeStore, config: RevalidationConfig) -> Self {
+        Self {
+            pipeline,
+            store,
+            config,
+        }
+    }
+
+    /// Run passes forever, `interval` apart. Returns immediately when disabled.
+    pub async fn run(self) {
+        if self.config.interval.is_zero() {
+            log::info!("Node re-validation disabled");
+            return;
+        }
+
+        let mut ticker = tokio::time::interval(self.config.interval);
+        // The first tick fires immediately; skip it so startup is not delayed by a pass
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            let summary = self.run_once().await;
+            log::info!("Re-validation pass: {:?}", summary);
+        }
+    }
+
+    /// Re-validate every known node once, then evict those failing too often
+    pub async fn run_once(&self) -> RevalidationSummary {
+        let mut summary = RevalidationSummary::default();
+
+        let mut by_chain: HashMap<u64, Vec<NodeRecord>> = HashMap::new();
+        for record in self.store.all_records().await {
+            by_chain.entry(record.chain_id).or_default().push(record);
+        }
+
+        for (chain_id, records) in by_chain {
+            // Custom and chainlist-imported chains are kept on their records
+            let chain = get_chain_by_id(chain_id)
+                .filter(|chain| !chain.genesis_hash.is_empty())
+                .or_else(|| records.iter().find_map(|r| r.chain.clone()))
+                .filter(|chain| !chain.genesis_hash.is_empty());
+            let chain = match chain {
+                Some(chain) => chain,
+                None => {
+                    summary.skipped += records.len();
+                    continue;
+                }
+            };
+
+            let reference_block = match self.pipeline.reference_block(&chain, &UserConfig::default()).await {
+                Ok(block) => block,
+                Err(e) => {
+                    log::warn!("Skipping re-validation of chain {}: {}", chain_id, e);
+                    summary.skipped += records.len();
+                    continue;
+                }
+            };
+
+            let checkpoint = self
+                .pipeline
+                .reference_checkpoint(&chain, &UserConfig::default(), reference_block, self.config.sync_tolerance)
+                .await;
+
+            let results: Vec<(String, Result<ValidatedNode, Error>)> = stream::iter(records)
+                .map(|record| {
+                    let chain = &chain;
+                    let checkpoint = checkpoint.as_ref();
+                    async move {
+                        let url = record.node.url;
+                        let result = if url.starts_with("ws://") || url.starts_with("wss://") {
+                            self.pipeline
+                                .ws_validator
+                                .validate(
+                                    &url,
+                                    chain.id,
+                                    &chain.genesis_hash,
+                                    reference_block,
+                                    self.config.sync_tolerance,
+                                    checkpoint,
+                                )
+                                .await
+                        } else {
+                            self.pipeline
+                                .http_validator
+                                .validate(
+                                    &url,
+                                    chain.id,
+                                    &chain.genesis_hash,
+                                    reference_block,
+                                    self.config.sync_tolerance,
+                                    checkpoint,
+                                )
+                                .await
+                        };
+                        (url, result)
+                    }
+                })
+                .buffer_unordered(self.config.concurrency)
+                .collect()
+                .await;
+
+            let max_failures = self.config.max_failures;
+            self.store
+                .update(|db| {
+                    for (url, result) in &results {
+                        summary.checked += 1;
+                        match result {
+                            Ok(node) => {
+                                summary.healthy += 1;
+                                db.record_success(&chain, node, None);
+                            }
+                            Err(e) => {
+                                summary.failed += 1;
+                                db.record_failure(chain_id, url, e);
+                                if db.get(chain_id, url).is_some_and(|r| r.consecutive_failures >= max_failures) {
+                                    log::debug!("Evicting {} after {} failed checks", url, max_failures);
+                                    db.remove(chain_id, url);
+                                    summary.evicted += 1;
+                                }
+                            }
+                        }
+                    }
+                })
+                .await;
+        }
+
+        summary
+    }
+}
@@ -0,0 +1,762 @@
+use crate::chains::Chain;
+use crate::config::{Protocol, UserConfig};
+use crate::error::Error;
+use crate::discovery::{Candidate, CertificateInfo, Discovery, DiscoveryQuery, SourceReport};
+use crate::store::NodeStore;
+use crate::validator::capabilities::supports_tracing;
+use crate::validator::tls::TlsInfo;
+use crate::validator::{archive::ArchiveValidator, http::HttpValidator, ws::WsValidator, Checkpoint, ValidatedNode};
+use futures::future::join_all;
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NodeType {
+    Full,
+    Archive,
+    Bulk,
+    /// Nodes exposing debug_/trace_ namespaces
+    Trace,
+}
+
+impl NodeType {
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "full" => Some(NodeType::Full),
+            "archive" => Some(NodeType::Archive),
+            "bulk" => Some(NodeType::Bulk),
+            "trace" => Some(NodeType::Trace),
+            _ => None,
+        }
+    }
+
+    /// How many validated nodes a search of this type returns
+    pub fn target_count(&self, config: &UserConfig) -> usize {
+        match self {
+            NodeType::Full | NodeType::Archive | NodeType::Trace => config.default_count as usize,
+            NodeType::Bulk => 50,
+        }
+    }
+}
+
+/// A single search: which chain, what kind of node, where, and the settings
+/// (count, protocol, tolerances, filters) to validate with
+#[derive(Debug, Clone)]
+pub struct SearchRequest {
+    pub chain: Chain,
+    pub node_type: NodeType,
+    /// Two-letter country code, `None` for all locations
+    pub country_code: Option<String>,
+    pub config: UserConfig,
+}
+
+impl SearchRequest {
+    /// Request with default settings across all locations
+    pub fn new(chain: Chain, node_type: NodeType) -> Self {
+        Self {
+            chain,
+            node_type,
+            country_code: None,
+            config: UserConfig::default(),
+        }
+    }
+}
+
+/// Blocks below the reference height at which candidates' block hashes are
+/// compared, at least the sync tolerance
+pub const FORK_CHECK_MARGIN: u64 = 64;
+
+/// Candidates asked for their chain ID at once before validation
+const CHAIN_PRECHECK_CONCURRENCY: usize = 50;
+
+/// The user's reference RPCs for the chain, otherwise the registry's
+fn reference_rpcs(chain: &Chain, config: &UserConfig) -> Vec<String> {
+    match config.get_reference_rpcs(chain.id) {
+        [] => chain.reference_rpcs(),
+        rpcs => rpcs.to_vec(),
+    }
+}
+
+/// Median of `heights`, the upper one for an even count. Sorts in place.
+pub fn median(heights: &mut [u64]) -> Option<u64> {
+    heights.sort_unstable();
+    heights.get(heights.len() / 2).copied()
+}
+
+/// Scheme, port and path of a URL, e.g. `ws:8545/ws`
+fn endpoint_label(url: &str) -> String {
+    match url::Url::parse(url) {
+        Ok(parsed) => format!(
+            "{}:{}{}",
+            parsed.scheme(),
+            parsed.port_or_known_default().unwrap_or(0),
+            parsed.path().trim_end_matches('/')
+        ),
+        Err(_) => url.to_string(),
+    }
+}
+
+/// What the reference RPCs reported for the checkpoint block's hash
+#[derive(Debug, Clone, PartialEq)]
+pub enum CheckpointVote {
+    /// More than half of the answering RPCs reported this hash
+    Agreed(Checkpoint),
+    /// The answering RPCs split without a majority, so no side is trusted
+    Disputed { block: u64 },
+    /// The chain is too short or no reference RPC answered
+    Unavailable,
+}
+
+impl CheckpointVote {
+    /// Tally the hashes the reference RPCs returned for `block`
+    pub fn tally(block: u64, hashes: Vec<String>) -> Self {
+        let responders = hashes.len();
+        let mut votes: BTreeMap<String, usize> = BTreeMap::new();
+        for hash in hashes {
+            *votes.entry(hash).or_default() += 1;
+        }
+
+        match votes.into_iter().find(|(_, count)| count * 2 > responders) {
+            Some((hash, _)) => CheckpointVote::Agreed(Checkpoint { block, hash }),
+            None if responders == 0 => CheckpointVote::Unavailable,
+            None => CheckpointVote::Disputed { block },
+        }
+    }
+
+    pub fn checkpoint(self) -> Option<Checkpoint> {
+        match self {
+            CheckpointVote::Agreed(checkpoint) => Some(checkpoint),
+            _ => None,
+        }
+    }
+}
+
+/// Block height agreed on by the reference RPCs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReferenceHeight {
+    /// Median of the heights reported by the responding RPCs
+    pub block: u64,
+    pub responded: usize,
+    pub queried: usize,
+}
+
+/// How candidates were whittled down during one search
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SearchDiagnostics {
+    /// Height candidates were checked against
+    pub reference_block: Option<u64>,
+    pub references_responded: usize,
+    /// Block whose hash candidates were checked against, `None` if skipped
+    pub checkpoint_block: Option<u64>,
+    /// Block the reference RPCs split on without a majority hash; the fork
+    /// check was skipped
+    pub checkpoint_disputed: Option<u64>,
+    /// No reference RPC answered; the median candidate height was used instead
+    pub reference_fallback: bool,
+    /// Matches, pages and failures per discovery source
+    pub sources: BTreeMap<&'static str, SourceReport>,
+    /// Candidates reported by more than one source, merged into one
+    pub duplicates: usize,
+    /// Unique candidates returned by all sources across all fetched pages
+    pub candidates_found: usize,
+    /// Dropped because they were not on a JSON-RPC port or behind TLS, or
+    /// were imported URLs of the other protocol
+    pub filtered_by_port: usize,
+    /// Dropped by the network filter or provider exclusions before validation
+    pub filtered_by_provider: usize,
+    /// Usable candidates left unvalidated once the `count × 3` budget was reached
+    pub over_budget: usize,
+    /// Validation and archive failures, keyed by `Error::reason()`
+    pub rejections: BTreeMap<&'static str, usize>,
+    /// Valid nodes running a client other than the user's filter
+    pub client_filtered: usize,
+    /// Valid nodes without a verifiable certificate while `valid_tls_only` is set
+    pub no_valid_tls: usize,
+    /// Valid nodes dropped for unlocked accounts or admin namespaces
+    pub exposed_excluded: usize,
+    /// Working WebSocket endpoints by variant, e.g. `ws:8546` or `wss:443/ws`
+    pub ws_endpoints: BTreeMap<String, usize>,
+    /// WebSocket nodes that did not stream a consistent `newHeads` header
+    pub no_subscription: usize,
+    /// Archive candidates whose state does not go back far enough
+    pub not_archive: usize,
+    /// Trace candidates without any debug_/trace_ method
+    pub no_trace_support: usize,
+    /// Nodes passing every check, before truncating to the requested count
+    pub passed: usize,
+    pub returned: usize,
+    /// Returned nodes per hosting provider, `unknown` without host metadata
+    pub providers: BTreeMap<String, usize>,
+}
+
+impl SearchDiagnostics {
+    pub fn rejected(&self, reason: &str) -> usize {
+        self.rejections.get(reason).copied().unwrap_or(0)
+    }
+
+    fn reject(&mut self, error: &Error) {
+        *self.rejections.entry(error.reason()).or_default() += 1;
+    }
+}
+
+/// Validated nodes together with how the search arrived at them
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchOutcome {
+    pub nodes: Vec<ValidatedNode>,
+    pub diagnostics: SearchDiagnostics,
+}
+
+/// Discovery sources plus the validators, shared by the bot and the CLI
+#[derive(Clone)]
+pub struct SearchPipeline {
+    pub discovery: Discovery,
+    pub http_validator: Arc<HttpValidator>,
+    pub ws_validator: Arc<WsValidator>,
+    pub archive_validator: Arc<ArchiveValidator>,
+    /// Node history updated with every search's validation results
+    pub store: Option<NodeStore>,
+}
+
+impl SearchPipeline {
+    pub fn new(discovery: Discovery) -> Self {
+        Self {
+            discovery,
+            http_validator: Arc::new(HttpValidator::new()),
+            ws_validator: Arc::new(WsValidator::new()),
+            archive_validator: Arc::new(ArchiveValidator::new()),
+            store: None,
+        }
+    }
+
+    /// Take candidates from `discovery` instead, e.g. an imported list,
+    /// keeping the validators and store
+    pub fn with_discovery(mut self, discovery: Discovery) -> Self {
+        self.discovery = discovery;
+        self
+    }
+
+    /// Record validation results in `store`
+    pub fn with_store(mut self, store: NodeStore) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Find nodes on the discovery sources and return those passing validation for the
+    /// request's node type, fastest first, with a funnel report of the rest
+    pub async fn search(&self, request: &SearchRequest) -> Result<SearchOutcome, Error> {
+        let config = &request.config;
+        let chain = &request.chain;
+        let node_type = request.node_type;
+        let country_code = request.country_code.as_deref();
+
+        // Every candidate would pass a genesis check against an empty hash
+        if chain.genesis_hash.is_empty() {
+            return Err(Error::GenesisUnpinned { chain_id: chain.id });
+        }
+
+        // Without any reachable reference, validate without a sync check and
+        // judge sync against the median candidate that passes the genesis check
+        let reference = self.reference_height(chain, config).await;
+        let (reference_block, sync_tolerance) = match &reference {
+            Ok(reference) => (reference.block, config.sync_tolerance),
+            Err(e) => {
+                log::warn!("{}; falling back to candidate heights for chain {}", e, chain.id);
+                (0, u64::MAX)
+            }
+        };
+        let checkpoint_vote = match &reference {
+            Ok(reference) => {
+                self.checkpoint_vote(chain, config, reference.block, sync_tolerance)
+                    .await
+            }
+            Err(_) => CheckpointVote::Unavailable,
+        };
+        let checkpoint_disputed = match checkpoint_vote {
+            CheckpointVote::Disputed { block } => Some(block),
+            _ => None,
+        };
+        let checkpoint = checkpoint_vote.checkpoint();
+        let checkpoint = checkpoint.as_ref();
+
+        // Determine how many nodes to validate
+        let target_count = node_type.target_count(config);
+        // Validate more than requested to account for failures
+        let candidate_count = target_count * 3;
+
+        // Query every source, each paging until we have enough candidates or hit the credit budget
+        let query = DiscoveryQuery {
+            chain_id: chain.id,
+            country_code: country_code.map(String::from),
+            min_results: candidate_count,
+            max_pages: config.shodan_max_pages,
+            shodan: config.get_shodan_query(chain.id),
+        };
+        let search = self.discovery.search(&query).await?;
+
+        log::info!(
+            "Discovery returned {} unique candidates for chain {} ({} duplicates): {:?}",
+            search.candidates.len(),
+            chain.id,
+            search.duplicates,
+            search.sources
+        );
+
+        let mut diagnostics = SearchDiagnostics {
+            reference_block: reference.as_ref().ok().map(|r| r.block),
+            references_responded: reference.as_ref().map(|r| r.responded).unwrap_or(0),
+            reference_fallback: reference.is_err(),
+            checkpoint_block: checkpoint.map(|c| c.block),
+            checkpoint_disputed,
+            sources: search.sources,
+            duplicates: search.duplicates,
+            candidates_found: search.candidates.len(),
+            ..Default::default()
+        };
+
+        if search.candidates.is_empty() {
+            return Ok(SearchOutcome {
+                nodes: vec![],
+                diagnostics,
+            });
+        }
+
+        // HTTP needs the RPC port itself or a TLS proxy in front of it; WebSocket
+        // candidates come from any of them and are probed for their real
+        // endpoint, so one per IP is enough. Imported ports are taken as given,
+        // imported URLs only need the right scheme.
+        let mut seen_ips = std::collections::HashSet::new();
+        let filtered: Vec<Candidate> = search
+            .candidates
+            .into_iter()
+            .filter(|r| match (config.protocol, &r.endpoint) {
+                (Protocol::Http, Some(url)) => url.starts_with("http"),
+                (Protocol::Ws, Some(url)) => url.starts_with("ws"),
+                (Protocol::Http, None) => r.listed || r.is_http_port() || r.is_tls(),
+                (Protocol::Ws, None) => {
+                    (r.listed || r.is_http_port() || r.is_ws_port() || r.is_tls()) && seen_ips.insert(r.ip.clone())
+                }
+            })
+            .collect();
+        diagnostics.filtered_by_port = diagnostics.candidates_found - filtered.len();
+
+        // Residential-only searches and excluded providers cost no validation
+        let before_provider = filtered.len();
+        let filtered: Vec<Candidate> = filtered
+            .into_iter()
+            .filter(|r| config.accepts_host(r.host.as_ref()))
+            .collect();
+        diagnostics.filtered_by_provider = before_provider - filtered.len();
+
+        // Only Shodan can search by chain; nodes of other chains from the
+        // other sources would otherwise use up the validation budget
+        let filtered = self
+            .precheck_chain(filtered, chain.id, config.protocol, &mut diagnostics)
+            .await;
+        diagnostics.over_budget = filtered.len().saturating_sub(candidate_count);
+
+        // Validate nodes in parallel
+        let validation_futures: Vec<_> = filtered
+            .into_iter()
+            .take(candidate_count)
+            .map(|result| async move {
+                let url = match (&result.endpoint, config.protocol) {
+                    (Some(url), _) => url.clone(),
+                    (None, Protocol::Http) if !result.is_tls() => result.http_url(),
+                    (None, Protocol::Http) => {
+                        let urls = result.https_urls();
+                        match self.http_validator.find_endpoint(&urls).await {
+                            Ok(url) => url,
+                            Err(e) => {
+                                log::debug!("No HTTPS endpoint on {}:{}: {}", result.ip, result.port, e);
+                                return (urls[0].clone(), result.country_code, Err(e));
+                            }
+                        }
+                    }
+                    (None, Protocol::Ws) => {
+                        let urls = result.ws_urls(&config.ws_ports, &config.ws_paths);
+                        match self.ws_validator.find_endpoint(&urls).await {
+                            Ok(url) => url,
+                            Err(e) => {
+                                log::debug!("No WebSocket endpoint on {}: {}", result.ip, e);
+                                return (urls[0].clone(), result.country_code, Err(e));
+                            }
+                        }
+                    }
+                };
+
+                let validation_result = match config.protocol {
+                    Protocol::Http => {
+                        self.http_validator
+                            .validate(
+                                &url,
+                                chain.id,
+                                &chain.genesis_hash,
+                                reference_block,
+                                sync_tolerance,
+                                checkpoint,
+                            )
+                            .await
+                    }
+                    Protocol::Ws => {
+                        self.ws_validator
+                            .validate(
+                                &url,
+                                chain.id,
+                                &chain.genesis_hash,
+                                reference_block,
+                                sync_tolerance,
+                                checkpoint,
+                            )
+                            .await
+                    }
+                };
+
+                let validation_result = match validation_result {
+                    Ok(node) => {
+                        let mut node = self.check_tls(node, result.certificate.as_ref()).await;
+                        node.host = result.host_info();
+                        Ok(node)
+                    }
+                    Err(e) => {
+                        log::debug!("Rejected {}: {}", url, e);
+                        Err(e)
+                    }
+                };
+                (url, result.country_code, validation_result)
+            })
+            .collect();
+        let mut validations = join_all(validation_futures).await;
+
+        // The backlog asked for the highest candidate height here. The median
+        // is used instead: candidates are untrusted, and with the highest one
+        // a single node reporting a far-future height would mark every honest
+        // node as out of sync. The median holds as long as most candidates
+        // that pass the genesis check are honest.
+        if reference.is_err() {
+            let mut heights: Vec<u64> = validations
+                .iter()
+                .filter_map(|(_, _, r)| r.as_ref().ok().map(|n| n.block_number))
+                .collect();
+            if let Some(median) = median(&mut heights) {
+                diagnostics.reference_block = Some(median);
+                for (_, _, result) in validations.iter_mut() {
+                    if let Ok(node) = result {
+                        let behind = median.abs_diff(node.block_number);
+                        if behind > config.sync_tolerance {
+                            *result = Err(Error::NotSynced {
+                                behind,
+                                tolerance: config.sync_tolerance,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(store) = &self.store {
+            store
+                .update(|db| {
+                    for (url, country_code, result) in &validations {
+                        match result {
+                            Ok(node) => db.record_success(chain, node, country_code.as_deref()),
+                            Err(e) => db.record_failure(chain.id, url, e),
+                        }
+                    }
+                })
+                .await;
+        }
+
+        if config.protocol == Protocol::Ws {
+            for (_, _, result) in &validations {
+                if let Ok(node) = result {
+                    *diagnostics.ws_endpoints.entry(endpoint_label(&node.url)).or_default() += 1;
+                }
+            }
+        }
+
+        let mut results = Vec::new();
+        for (_, _, result) in validations {
+            match result {
+                Ok(node) if !config.accepts_client(node.client.as_ref()) => diagnostics.client_filtered += 1,
+                Ok(node) if config.valid_tls_only && !node.tls.as_ref().is_some_and(|t| t.valid) => {
+                    diagnostics.no_valid_tls += 1
+                }
+                Ok(node) => results.push(node),
+                Err(e) => diagnostics.reject(&e),
+            }
+        }
+
+        // Audit for unlocked accounts/admin namespaces before anything else uses the node
+        let audits = results.into_iter().map(|node| async move {
+            match config.protocol {
+                Protocol::Http => self.http_validator.audit_security(node).await,
+                Protocol::Ws => self.ws_validator.audit_security(node).await,
+            }
+        });
+        let audited = join_all(audits).await;
+        let before_audit = audited.len();
+        let results: Vec<ValidatedNode> = audited
+            .into_iter()
+            .filter(|n| !(config.exclude_exposed && n.is_exposed()))
+            .collect();
+        diagnostics.exposed_excluded = before_audit - results.len();
+
+        // WebSocket users rely on eth_subscribe, so check newHeads actually streams
+        let results = if config.protocol == Protocol::Ws && config.check_subscriptions {
+            let reference_block = diagnostics.reference_block.unwrap_or(0);
+            let checks = results.into_iter().map(|node| {
+                self.ws_validator
+                    .check_subscription(node, chain.block_time_ms, reference_block, config.sync_tolerance)
+            });
+            let checked = join_all(checks).await;
+            let before_check = checked.len();
+            let results: Vec<ValidatedNode> = checked
+                .into_iter()
+                .filter(|n| n.supports_subscriptions == Some(true))
+                .collect();
+            diagnostics.no_subscription = before_check - results.len();
+            results
+        } else {
+            results
+        };
+
+        // For archive nodes, additionally check archive capability
+        let mut final_results = if node_type == NodeType::Archive {
+            let archive_futures: Vec<_> = results
+                .into_iter()
+                .map(|node| self.archive_validator.validate_archive(node, chain))
+                .collect();
+
+            let mut archive_nodes = Vec::new();
+            for result in join_all(archive_futures).await {
+                match result {
+                    Ok(node) if node.oldest_state_block.is_some_and(|b| b <= chain.archive_bound(config.archive_from_block)) => {
+                        archive_nodes.push(node)
+                    }
+                    Ok(_) => diagnostics.not_archive += 1,
+                    Err(e) => diagnostics.reject(&e),
+                }
+            }
+            archive_nodes
+        } else {
+            results
+        };
+
+        // Trace/debug nodes are selected by their capabilities, so probe everything
+        if node_type == NodeType::Trace {
+            let probed = self
+                .probe_capabilities(config.protocol, final_results, &config.capability_methods)
+                .await;
+            let before_probe = probed.len();
+            final_results = probed
+                .into_iter()
+                .filter(|n| supports_tracing(&n.capabilities))
+                .collect();
+            diagnostics.no_trace_support = before_probe - final_results.len();
+        }
+
+        diagnostics.passed = final_results.len();
+        log::info!("Search diagnostics for chain {}: {:?}", chain.id, diagnostics);
+
+        // Sort by latency
+        final_results.sort_by_key(|n| n.latency_ms);
+
+        // Limit results
+        final_results.truncate(target_count);
+
+        // Otherwise only probe the nodes we are about to return
+        if node_type != NodeType::Trace {
+            final_results = self
+                .probe_capabilities(config.protocol, final_results, &config.capability_methods)
+                .await;
+        }
+
+        if let Some(store) = &self.store {
+            store
+                .update(|db| {
+                    for node in &final_results {
+                        db.update_details(chain.id, node);
+                    }
+                })
+                .await;
+        }
+
+        diagnostics.returned = final_results.len();
+        for node in &final_results {
+            let provider = node.host.as_ref().and_then(|h| h.provider()).unwrap_or("unknown");
+            *diagnostics.providers.entry(provider.to_string()).or_default() += 1;
+        }
+
+        Ok(SearchOutcome {
+            nodes: final_results,
+            diagnostics,
+        })
+    }
+
+    /// Median block height across the chain's reference RPCs, queried
+    /// concurrently. The user's RPCs replace the registry's default and
+    /// fallback RPCs. Fails only when none of them answer.
+    pub async fn reference_height(&self, chain: &Chain, config: &UserConfig) -> Result<ReferenceHeight, Error> {
+        let reference_rpcs = reference_rpcs(chain, config);
+
+        let queries = reference_rpcs.iter().map(|rpc| async move {
+            self.http_validator
+                .get_current_block(rpc)
+                .await
+                .inspect_err(|e| log::warn!("Reference RPC {} failed: {}", rpc, e))
+        });
+
+        let mut last_error = Error::InvalidResponse("no reference RPC configured".to_string());
+        let mut heights = Vec::new();
+        for result in join_all(queries).await {
+            match result {
+                Ok(height) => heights.push(height),
+                Err(e) => last_error = e,
+            }
+        }
+
+        let Some(block) = median(&mut heights) else {
+            return Err(Error::ReferenceUnavailable(Box::new(last_error)));
+        };
+
+        Ok(ReferenceHeight {
+            block,
+            responded: heights.len(),
+            queried: reference_rpcs.len(),
+        })
+    }
+
+    /// Median reference block height, see [`Self::reference_height`]
+    pub async fn reference_block(&self, chain: &Chain, config: &UserConfig) -> Result<u64, Error> {
+        self.reference_height(chain, config).await.map(|r| r.block)
+    }
+
+    /// Hash of a block far enough below `reference_block` that every node
+    /// within `sync_tolerance` has it and reorgs are unlikely, as reported by
+    /// a strict majority of the answering reference RPCs. `None` when no
+    /// reference RPC answers or they split without a majority.
+    pub async fn reference_checkpoint(
+        &self,
+        chain: &Chain,
+        config: &UserConfig,
+        reference_block: u64,
+        sync_tolerance: u64,
+    ) -> Option<Checkpoint> {
+        self.checkpoint_vote(chain, config, reference_block, sync_tolerance)
+            .await
+            .checkpoint()
+    }
+
+    /// Ask every reference RPC for the checkpoint block's hash, see
+    /// [`Self::reference_checkpoint`]
+    pub async fn checkpoint_vote(
+        &self,
+        chain: &Chain,
+        config: &UserConfig,
+        reference_block: u64,
+        sync_tolerance: u64,
+    ) -> CheckpointVote {
+        let Some(block) = reference_block.checked_sub(FORK_CHECK_MARGIN.max(sync_tolerance)) else {
+            return CheckpointVote::Unavailable;
+        };
+        let reference_rpcs = reference_rpcs(chain, config);
+        let queries = reference_rpcs.iter().map(|rpc| async move {
+            self.http_validator
+                .get_block_hash(rpc, block)
+                .await
+                .inspect_err(|e| log::warn!("Reference RPC {} failed to return block {}: {}", rpc, block, e))
+                .ok()
+        });
+
+        let hashes: Vec<String> = join_all(queries).await.into_iter().flatten().collect();
+        let vote = CheckpointVote::tally(block, hashes.clone());
+        match &vote {
+            CheckpointVote::Disputed { .. } => log::warn!(
+                "Reference RPCs have no majority for block {} of chain {}, skipping the fork check: {:?}",
+                block,
+                chain.id,
+                hashes
+            ),
+            CheckpointVote::Agreed(checkpoint) if hashes.iter().any(|h| *h != checkpoint.hash) => {
+                log::warn!("Reference RPCs disagree on block {} of chain {}: {:?}", block, chain.id, hashes)
+            }
+            _ => {}
+        }
+        vote
+    }
+
+    /// Record certificate details on HTTPS/WSS nodes. WebSocket connections
+    /// already verify certificates, so only HTTPS needs a strict request.
+    async fn check_tls(&self, mut node: ValidatedNode, certificate: Option<&CertificateInfo>) -> ValidatedNode {
+        let valid = if node.url.starts_with("https://") {
+            self.http_validator.has_valid_certificate(&node.url).await
+        } else if node.url.starts_with("wss://") {
+            true
+        } else {
+            return node;
+        };
+
+        let certificate = certificate.cloned().unwrap_or_default();
+        node.tls = Some(TlsInfo {
+            valid,
+            expires_at: certificate.expires_at,
+            issuer: certificate.issuer,
+        });
+        node
+    }
+
+    /// Ask candidates whose source did not match the chain for `eth_chainId`
+    /// over plain HTTP, dropping those on another chain. For HTTP searches
+    /// candidates that do not answer are dropped too, as validation would
+    /// reject them; for WebSocket searches they are kept, since the port may
+    /// serve WebSocket only. TLS candidates and imported URLs are kept as is.
+    async fn precheck_chain(
+        &self,
+        candidates: Vec<Candidate>,
+        chain_id: u64,
+        protocol: Protocol,
+        diagnostics: &mut SearchDiagnostics,
+    ) -> Vec<Candidate> {
+        let checks = candidates.into_iter().map(|candidate| async move {
+            if candidate.chain_matched || candidate.endpoint.is_some() || candidate.is_tls() {
+                return (candidate, Ok(()));
+            }
+            let result = match self.http_validator.get_chain_id(&candidate.http_url()).await {
+                Ok(id) if id == chain_id => Ok(()),
+                Ok(actual) => Err(Error::ChainIdMismatch { expected: chain_id, actual }),
+                Err(_) if protocol == Protocol::Ws => Ok(()),
+                Err(e) => Err(e),
+            };
+            (candidate, result)
+        });
+        let checked: Vec<(Candidate, Result<(), Error>)> =
+            stream::iter(checks).buffered(CHAIN_PRECHECK_CONCURRENCY).collect().await;
+
+        let mut kept = Vec::new();
+        for (candidate, result) in checked {
+            match result {
+                Ok(()) => kept.push(candidate),
+                Err(e) => diagnostics.reject(&e),
+            }
+        }
+        kept
+    }
+
+    async fn probe_capabilities(
+        &self,
+        protocol: Protocol,
+        nodes: Vec<ValidatedNode>,
+        methods: &[String],
+    ) -> Vec<ValidatedNode> {
+        let probes = nodes.into_iter().map(|node| async move {
+            match protocol {
+                Protocol::Http => self.http_validator.probe_capabilities(node, methods).await,
+                Protocol::Ws => self.ws_validator.probe_capabilities(node, methods).await,
+            }
+        });
+
+        join_all(probes).await
+    }
+}
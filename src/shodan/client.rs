@@ -1,41 +1,70 @@
+use crate::discovery::hosting::{HostInfo, ScannedRpc};
+use crate::discovery::{Candidate, CertificateInfo, DiscoveryQuery, DiscoveryResult, DiscoverySource, RateLimiter};
+use crate::error::Error;
+use crate::validator::parse_hex_u64;
+use crate::validator::tls::parse_cert_time;
+use futures::future::BoxFuture;
 use reqwest::Client;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
-use std::time::{Duration, Instant};
-use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::collections::HashMap;
+use std::env;
+use std::time::Duration;
 
 const SHODAN_API_BASE: &str = "https://api.shodan.io";
 const RATE_LIMIT_INTERVAL: Duration = Duration::from_secs(1);
+/// Shodan returns at most 100 matches per page of `/shodan/host/search`
+pub const SHODAN_PAGE_SIZE: usize = 100;
+/// Facets counted by default: where the nodes are and who hosts them
+pub const DEFAULT_FACETS: [&str; 3] = ["country", "org", "asn"];
+/// Values returned per facet
+const FACET_SIZE: usize = 10;
 
-#[derive(Debug, Clone)]
-pub struct ShodanResult {
-    pub ip: String,
-    pub port: u16,
-    pub country_code: Option<String>,
+#[derive(Debug, Deserialize)]
+struct ShodanSearchResponse {
+    matches: Vec<ShodanMatch>,
+    #[serde(default)]
+    total: u64,
 }
 
-impl ShodanResult {
-    pub fn http_url(&self) -> String {
-        format!("http://{}:{}", self.ip, self.port)
-    }
+#[derive(Debug, Deserialize)]
+struct ShodanApiInfo {
+    query_credits: u32,
+}
 
-    pub fn ws_url(&self) -> String {
-        // WebSocket typically runs on port 8546, regardless of which port Shodan found
-        format!("ws://{}:8546", self.ip)
-    }
+#[derive(Debug, Deserialize)]
+struct ShodanCountResponse {
+    #[serde(default)]
+    total: u64,
+    #[serde(default)]
+    facets: HashMap<String, Vec<ShodanFacetValue>>,
+}
 
-    pub fn is_http_port(&self) -> bool {
-        self.port == 8545
-    }
+#[derive(Debug, Deserialize)]
+struct ShodanFacetValue {
+    count: u64,
+    /// A string for most facets, a number for `port`
+    value: serde_json::Value,
+}
 
-    pub fn is_ws_port(&self) -> bool {
-        self.port == 8546
-    }
+/// Match counts for a query, broken down by facet, without fetching (or
+/// paying for) the matches themselves
+#[derive(Debug, Clone, Default)]
+pub struct ShodanFacets {
+    pub total: u64,
+    /// Most common values with their match counts, per requested facet
+    pub facets: Vec<(String, Vec<(String, u64)>)>,
 }
 
-#[derive(Debug, Deserialize)]
-struct ShodanSearchResponse {
-    matches: Vec<ShodanMatch>,
+impl ShodanFacets {
+    /// Counts of one facet, most common first
+    pub fn values(&self, facet: &str) -> &[(String, u64)] {
+        self.facets
+            .iter()
+            .find(|(name, _)| name == facet)
+            .map(|(_, values)| values.as_slice())
+            .unwrap_or(&[])
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -43,24 +72,118 @@ struct ShodanMatch {
     ip_str: String,
     port: u16,
     location: ShodanLocation,
+    #[serde(default)]
+    hostnames: Vec<String>,
+    ssl: Option<ShodanSsl>,
+    org: Option<String>,
+    isp: Option<String>,
+    asn: Option<String>,
+    /// When Shodan collected the banner, e.g. `2024-01-15T10:23:45.123456`
+    timestamp: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    cloud: Option<ShodanCloud>,
     ethereum_rpc: Option<EthereumRpc>,
 }
 
+impl ShodanMatch {
+    fn into_candidate(self) -> Candidate {
+        let cert = self.ssl.map(|ssl| ssl.cert.unwrap_or_default());
+
+        let mut candidate = Candidate::new("shodan", self.ip_str, self.port);
+        candidate.country_code = self.location.country_code;
+        candidate.host = Some(HostInfo {
+            org: self.org,
+            asn: self.asn,
+            isp: self.isp,
+            city: self.location.city,
+            hostnames: Vec::new(),
+            cloud: self.cloud.and_then(|c| c.provider),
+            tags: self.tags,
+            last_update: self.timestamp.as_deref().and_then(parse_timestamp),
+            rpc: self.ethereum_rpc.map(|rpc| ScannedRpc {
+                client: rpc.client,
+                version: rpc.version,
+                block_number: rpc.block_number.as_ref().and_then(block_number),
+            }),
+        });
+        let common_name = cert.as_ref().and_then(|c| ShodanCert::field(&c.subject, "CN"));
+        candidate.add_hostnames(common_name.into_iter().chain(self.hostnames));
+        candidate.certificate = cert.map(|c| CertificateInfo {
+            expires_at: c.expires.as_deref().and_then(parse_cert_time),
+            issuer: ShodanCert::field(&c.issuer, "O").or_else(|| ShodanCert::field(&c.issuer, "CN")),
+        });
+        candidate
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ShodanSsl {
+    cert: Option<ShodanCert>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ShodanCert {
+    expires: Option<String>,
+    #[serde(default)]
+    issuer: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    subject: HashMap<String, serde_json::Value>,
+}
+
+impl ShodanCert {
+    fn field(map: &HashMap<String, serde_json::Value>, key: &str) -> Option<String> {
+        map.get(key).and_then(|v| v.as_str()).map(String::from)
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct ShodanLocation {
     country_code: Option<String>,
+    city: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ShodanCloud {
+    provider: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct EthereumRpc {
-    chain_id: Option<String>,
+    client: Option<String>,
+    version: Option<String>,
+    /// Hex quantity or plain number
+    block_number: Option<serde_json::Value>,
+}
+
+/// A banner timestamp as Unix time, via the certificate time parser
+fn parse_timestamp(s: &str) -> Option<u64> {
+    let digits: String = s.get(..19)?.chars().filter(char::is_ascii_digit).collect();
+    parse_cert_time(&format!("{}Z", digits))
+}
+
+fn block_number(value: &serde_json::Value) -> Option<u64> {
+    match value {
+        serde_json::Value::String(s) if s.starts_with("0x") => parse_hex_u64(s),
+        serde_json::Value::String(s) => s.parse().ok(),
+        value => value.as_u64(),
+    }
+}
+
+/// A facet value as text; ASNs, orgs and countries are strings, ports numbers
+fn facet_value(value: serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s,
+        value => value.to_string(),
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct ShodanClient {
     client: Client,
     api_key: String,
-    last_request: Arc<Mutex<Option<Instant>>>,
+    base_url: String,
+    rate_limiter: RateLimiter,
 }
 
 impl ShodanClient {
@@ -68,73 +191,160 @@ impl ShodanClient {
         Self {
             client: Client::new(),
             api_key,
-            last_request: Arc::new(Mutex::new(None)),
+            base_url: SHODAN_API_BASE.to_string(),
+            rate_limiter: RateLimiter::new(RATE_LIMIT_INTERVAL),
         }
     }
 
-    async fn rate_limit(&self) {
-        let mut last = self.last_request.lock().await;
-        if let Some(last_time) = *last {
-            let elapsed = last_time.elapsed();
-            if elapsed < RATE_LIMIT_INTERVAL {
-                tokio::time::sleep(RATE_LIMIT_INTERVAL - elapsed).await;
-            }
+    /// Client for `SHODAN_TOKEN`, if it is set
+    pub fn from_env() -> Option<Self> {
+        env::var("SHODAN_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty())
+            .map(Self::new)
+    }
+
+    /// Send requests to another API root, e.g. a proxy or a test server
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    fn error(message: String) -> Error {
+        Error::Discovery {
+            provider: "shodan",
+            message,
         }
-        *last = Some(Instant::now());
     }
 
-    pub async fn search_nodes(
-        &self,
-        chain_id: u64,
-        country_code: Option<&str>,
-    ) -> Result<Vec<ShodanResult>, String> {
-        self.rate_limit().await;
+    /// Remaining query credits on the API key. Every page of a filtered
+    /// search (`port:`, `country:`, ...) costs one, including the first.
+    pub async fn query_credits(&self) -> Result<u32, Error> {
+        let url = format!("{}/api-info?key={}", self.base_url, self.api_key);
+        let info: ShodanApiInfo = self.get(&url).await?;
+        Ok(info.query_credits)
+    }
 
-        let hex_id = format!("0x{:x}", chain_id);
+    /// Matches for the query's Shodan filters counted by `facets` (e.g.
+    /// `country`, `org`, `asn`), without spending query credits, so the
+    /// spread of nodes can be seen before any are validated
+    pub async fn facets(&self, query: &DiscoveryQuery, facets: &[&str]) -> Result<ShodanFacets, Error> {
+        let shodan_query = query.shodan.build(query.chain_id, query.country_code.as_deref());
+        let encoded_query: String = url::form_urlencoded::byte_serialize(shodan_query.as_bytes()).collect();
+        let requested: Vec<String> = facets.iter().map(|f| format!("{}:{}", f, FACET_SIZE)).collect();
+        let url = format!(
+            "{}/shodan/host/count?key={}&query={}&facets={}",
+            self.base_url,
+            self.api_key,
+            encoded_query,
+            requested.join(",")
+        );
 
-        // Use "Chain Id: 0x1" format (note capital I) - matches Geth response format
-        let mut query = format!("port:8545,8546 \"Chain Id: {}\"", hex_id);
+        let mut data: ShodanCountResponse = self.get(&url).await?;
+        Ok(ShodanFacets {
+            total: data.total,
+            facets: facets
+                .iter()
+                .map(|facet| {
+                    let values = data
+                        .facets
+                        .remove(*facet)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|v| (facet_value(v.value), v.count))
+                        .collect();
+                    (facet.to_string(), values)
+                })
+                .collect(),
+        })
+    }
+
+    /// Search for nodes with the query's Shodan filters, fetching further
+    /// result pages until at least `min_results` candidates are collected,
+    /// Shodan runs out of matches, or `max_pages` (capped by the remaining
+    /// query credits) is reached.
+    pub async fn search_nodes(&self, query: &DiscoveryQuery) -> Result<DiscoveryResult, Error> {
+        let filters = &query.shodan;
+        let shodan_query = filters.build(query.chain_id, query.country_code.as_deref());
+        log::debug!("Shodan query: {}", shodan_query);
+
+        let mut search = DiscoveryResult::default();
+        let mut page_limit = query.max_pages.max(1);
+
+        while search.pages_fetched < page_limit {
+            let page = search.pages_fetched + 1;
+            let data = self.fetch_page(&shodan_query, page).await?;
+            search.pages_fetched = page;
+            search.total = data.total;
 
-        if let Some(cc) = country_code {
-            query.push_str(&format!(" country:{}", cc));
+            let page_len = data.matches.len();
+            search.candidates.extend(data.matches.into_iter().map(|m| {
+                let mut candidate = m.into_candidate();
+                candidate.listed = filters.requests_port(candidate.port);
+                // A raw query may leave out the chain ID
+                candidate.chain_matched = filters.raw.is_none();
+                candidate
+            }));
+
+            let exhausted = page_len < SHODAN_PAGE_SIZE
+                || search.candidates.len() as u64 >= search.total;
+            if exhausted || search.candidates.len() >= query.min_results {
+                break;
+            }
+
+            // Only look up the credit balance once we know more pages are needed.
+            // This page is already paid for; each further one costs a credit.
+            if page == 1 && page_limit > 1 {
+                match self.query_credits().await {
+                    Ok(credits) => page_limit = page_limit.min(page.saturating_add(credits)),
+                    Err(e) => log::warn!("Could not fetch Shodan query credits: {}", e),
+                }
+            }
         }
 
+        Ok(search)
+    }
+
+    async fn fetch_page(&self, query: &str, page: u32) -> Result<ShodanSearchResponse, Error> {
         let encoded_query: String = url::form_urlencoded::byte_serialize(query.as_bytes()).collect();
         let url = format!(
-            "{}/shodan/host/search?key={}&query={}",
-            SHODAN_API_BASE,
+            "{}/shodan/host/search?key={}&query={}&page={}",
+            self.base_url,
             self.api_key,
-            encoded_query
+            encoded_query,
+            page
         );
+        self.get(&url).await
+    }
+
+    async fn get<T: DeserializeOwned>(&self, url: &str) -> Result<T, Error> {
+        self.rate_limiter.wait().await;
 
         let response = self
             .client
-            .get(&url)
+            .get(url)
             .timeout(Duration::from_secs(30))
             .send()
             .await
-            .map_err(|e| format!("Shodan request failed: {}", e))?;
+            .map_err(|e| Self::error(format!("request failed: {}", e.without_url())))?;
 
         if !response.status().is_success() {
-            return Err(format!("Shodan API error: {}", response.status()));
+            return Err(Self::error(format!("API error: {}", response.status())));
         }
 
-        let data: ShodanSearchResponse = response
+        response
             .json()
             .await
-            .map_err(|e| format!("Failed to parse Shodan response: {}", e))?;
+            .map_err(|e| Self::error(format!("failed to parse response: {}", e.without_url())))
+    }
+}
 
-        let results: Vec<ShodanResult> = data
-            .matches
-            .into_iter()
-            .map(|m| ShodanResult {
-                ip: m.ip_str,
-                port: m.port,
-                country_code: m.location.country_code,
-            })
-            .collect();
+impl DiscoverySource for ShodanClient {
+    fn name(&self) -> &'static str {
+        "shodan"
+    }
 
-        Ok(results)
+    fn search<'a>(&'a self, query: &'a DiscoveryQuery) -> BoxFuture<'a, Result<DiscoveryResult, Error>> {
+        Box::pin(self.search_nodes(query))
     }
 }
-
@@ -1,3 +1,5 @@
 pub mod client;
+pub mod query;
 
-pub use client::{ShodanClient, ShodanResult};
+pub use client::ShodanClient;
+pub use query::ShodanQuery;
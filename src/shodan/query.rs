@@ -0,0 +1,205 @@
+use crate::discovery::RPC_PORTS;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Per-chain changes to the Shodan query. The default query only matches
+/// banners Shodan enriched with the chain ID (Geth-style); products and
+/// banner text widen it to clients and L2 sequencers whose banners lack it,
+/// ASNs and organizations narrow it to those networks, and a raw query
+/// replaces it altogether.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ShodanQuery {
+    /// Ports searched in addition to `RPC_PORTS`
+    #[serde(default)]
+    pub ports: Vec<u16>,
+    /// Shodan `product:` values, e.g. `Nethermind`
+    #[serde(default)]
+    pub products: Vec<String>,
+    /// Banner text, e.g. `"jsonrpc"`
+    #[serde(default)]
+    pub banners: Vec<String>,
+    /// Autonomous systems, e.g. `AS24940`
+    #[serde(default)]
+    pub asns: Vec<String>,
+    /// Shodan `org:` values, e.g. `Hetzner Online GmbH`
+    #[serde(default)]
+    pub orgs: Vec<String>,
+    /// Replaces the generated query; `{chain_id}` and `{chain_id_hex}` are
+    /// substituted, and the country filter is added unless it has one
+    #[serde(default)]
+    pub raw: Option<String>,
+}
+
+impl ShodanQuery {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Parse filters written the way Shodan takes them:
+    /// `port:8547,9650 product:Nethermind asn:AS24940 org:"Hetzner Online GmbH" "banner text"`,
+    /// or `raw <query>` for a raw query
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        if let Some(raw) = text.strip_prefix("raw ") {
+            return Ok(Self {
+                raw: Some(raw.trim().to_string()),
+                ..Default::default()
+            });
+        }
+
+        let mut query = Self::default();
+        for (token, quoted) in tokenize(text)? {
+            let filter = if quoted { None } else { token.split_once(':') };
+            match filter {
+                None => query.banners.push(token),
+                Some(("port", ports)) => {
+                    for port in ports.split(',').filter(|p| !p.is_empty()) {
+                        query
+                            .ports
+                            .push(port.parse().map_err(|_| format!("invalid port '{}'", port))?);
+                    }
+                }
+                Some(("product", product)) => query.products.push(product.to_string()),
+                Some(("asn", asn)) => query.asns.push(normalize_asn(asn)?),
+                Some(("org", org)) => query.orgs.push(org.to_string()),
+                Some((filter, _)) => {
+                    return Err(format!(
+                        "unsupported filter '{}', use \"raw <query>\" for other Shodan filters",
+                        filter
+                    ))
+                }
+            }
+        }
+        Ok(query)
+    }
+
+    /// The Shodan search query for a chain:
+    /// `port:8545,8546,443 ("Chain Id: 0x89" OR "Chain Id: 137") country:DE`,
+    /// with extra ports, `OR`ed products and banners, and ASN/org filters
+    pub fn build(&self, chain_id: u64, country_code: Option<&str>) -> String {
+        let hex_id = format!("0x{:x}", chain_id);
+
+        let mut query = match &self.raw {
+            Some(raw) => raw
+                .replace("{chain_id_hex}", &hex_id)
+                .replace("{chain_id}", &chain_id.to_string()),
+            None => {
+                let mut ports = RPC_PORTS.to_vec();
+                for port in &self.ports {
+                    if !ports.contains(port) {
+                        ports.push(*port);
+                    }
+                }
+                let ports: Vec<String> = ports.iter().map(|p| p.to_string()).collect();
+
+                // Shodan's Ethereum banner reads "Chain Id: 0x1"; some list the decimal ID
+                let mut matches = vec![format!("\"Chain Id: {}\"", hex_id), format!("\"Chain Id: {}\"", chain_id)];
+                matches.extend(self.products.iter().map(|p| format!("product:\"{}\"", p)));
+                matches.extend(self.banners.iter().map(|b| format!("\"{}\"", b)));
+
+                let mut parts = vec![format!("port:{}", ports.join(",")), any_of(&matches)];
+                if !self.asns.is_empty() {
+                    let asns: Vec<String> = self.asns.iter().map(|a| format!("asn:{}", a)).collect();
+                    parts.push(any_of(&asns));
+                }
+                if !self.orgs.is_empty() {
+                    let orgs: Vec<String> = self.orgs.iter().map(|o| format!("org:\"{}\"", o)).collect();
+                    parts.push(any_of(&orgs));
+                }
+                parts.join(" ")
+            }
+        };
+
+        if let Some(cc) = country_code {
+            if !query.contains("country:") {
+                query.push_str(&format!(" country:{}", cc));
+            }
+        }
+        query
+    }
+
+    /// Whether candidates on `port` were asked for explicitly rather than
+    /// found on a default RPC port
+    pub fn requests_port(&self, port: u16) -> bool {
+        self.raw.is_some() || self.ports.contains(&port)
+    }
+}
+
+/// The filters in `parse`'s syntax
+impl fmt::Display for ShodanQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(raw) = &self.raw {
+            return write!(f, "raw {}", raw);
+        }
+
+        let mut parts = Vec::new();
+        if !self.ports.is_empty() {
+            let ports: Vec<String> = self.ports.iter().map(|p| p.to_string()).collect();
+            parts.push(format!("port:{}", ports.join(",")));
+        }
+        parts.extend(self.products.iter().map(|p| format!("product:{}", quote(p))));
+        parts.extend(self.asns.iter().map(|a| format!("asn:{}", a)));
+        parts.extend(self.orgs.iter().map(|o| format!("org:{}", quote(o))));
+        parts.extend(self.banners.iter().map(|b| format!("\"{}\"", b)));
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+/// One clause, parenthesized when it has alternatives
+fn any_of(clauses: &[String]) -> String {
+    match clauses {
+        [one] => one.clone(),
+        many => format!("({})", many.join(" OR ")),
+    }
+}
+
+fn quote(value: &str) -> String {
+    if value.contains(char::is_whitespace) {
+        format!("\"{}\"", value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// `24940` or `as24940` as `AS24940`
+fn normalize_asn(asn: &str) -> Result<String, String> {
+    let digits = asn.trim_start_matches(['A', 'S', 'a', 's']);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("invalid ASN '{}'", asn));
+    }
+    Ok(format!("AS{}", digits))
+}
+
+/// Split on whitespace outside double quotes; a token is quoted when it
+/// starts with a quote, so `"a:b"` is banner text but `org:"a b"` a filter
+fn tokenize(text: &str) -> Result<Vec<(String, bool)>, String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut in_quotes = false;
+
+    for c in text.chars() {
+        match c {
+            '"' => {
+                if current.is_empty() && !in_quotes {
+                    quoted = true;
+                }
+                in_quotes = !in_quotes;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push((std::mem::take(&mut current), quoted));
+                }
+                quoted = false;
+            }
+            c => current.push(c),
+        }
+    }
+    if in_quotes {
+        return Err("unbalanced quote".to_string());
+    }
+    if !current.is_empty() {
+        tokens.push((current, quoted));
+    }
+    Ok(tokens)
+}
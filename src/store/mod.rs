@@ -0,0 +1,213 @@
+pub mod storage;
+
+use crate::chains::{get_chain_by_id, Chain};
+use crate::error::Error;
+use crate::validator::ValidatedNode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub use storage::NodeStore;
+
+/// Checks kept per node; older entries are dropped
+pub const MAX_CHECK_HISTORY: usize = 100;
+
+/// Seconds since the Unix epoch
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Outcome of one validation of a node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckRecord {
+    pub at: u64,
+    /// `None` when the node passed, otherwise `Error::reason()` of the failure
+    #[serde(default)]
+    pub failure: Option<String>,
+    #[serde(default)]
+    pub latency_ms: Option<u64>,
+    #[serde(default)]
+    pub block_number: Option<u64>,
+}
+
+impl CheckRecord {
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// Everything known about one node of one chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeRecord {
+    pub chain_id: u64,
+    /// Chain the node was validated against when the registry cannot resolve
+    /// it with a genesis hash (custom and chainlist-imported chains), so
+    /// re-validation can check the node too
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain: Option<Chain>,
+    #[serde(default)]
+    pub country_code: Option<String>,
+    pub first_seen: u64,
+    /// Last time the node passed validation
+    pub last_seen: u64,
+    pub last_checked: u64,
+    #[serde(default)]
+    pub consecutive_failures: u32,
+    /// Node as of its last successful validation
+    pub node: ValidatedNode,
+    #[serde(default)]
+    pub checks: Vec<CheckRecord>,
+}
+
+impl NodeRecord {
+    pub fn is_healthy(&self) -> bool {
+        self.consecutive_failures == 0
+    }
+
+    /// How long the node has been seen alive, in seconds
+    pub fn lifetime(&self) -> u64 {
+        self.last_seen.saturating_sub(self.first_seen)
+    }
+
+    /// Share of recorded checks the node passed, 0.0–1.0
+    pub fn success_rate(&self) -> f64 {
+        if self.checks.is_empty() {
+            return 0.0;
+        }
+        let passed = self.checks.iter().filter(|c| c.passed()).count();
+        passed as f64 / self.checks.len() as f64
+    }
+
+    /// Mean latency over the successful checks in the history
+    pub fn average_latency_ms(&self) -> Option<u64> {
+        let latencies: Vec<u64> = self.checks.iter().filter_map(|c| c.latency_ms).collect();
+        if latencies.is_empty() {
+            return None;
+        }
+        Some(latencies.iter().sum::<u64>() / latencies.len() as u64)
+    }
+
+    fn push_check(&mut self, check: CheckRecord) {
+        self.last_checked = check.at;
+        self.checks.push(check);
+        if self.checks.len() > MAX_CHECK_HISTORY {
+            let excess = self.checks.len() - MAX_CHECK_HISTORY;
+            self.checks.drain(..excess);
+        }
+    }
+}
+
+/// Node records per chain, keyed by URL
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct NodeDb {
+    chains: HashMap<u64, HashMap<String, NodeRecord>>,
+}
+
+impl NodeDb {
+    /// Record a node that passed validation, creating its record on first sight
+    pub fn record_success(&mut self, chain: &Chain, node: &ValidatedNode, country_code: Option<&str>) {
+        let at = now();
+        let record = self
+            .chains
+            .entry(chain.id)
+            .or_default()
+            .entry(node.url.clone())
+            .or_insert_with(|| NodeRecord {
+                chain_id: chain.id,
+                chain: None,
+                country_code: None,
+                first_seen: at,
+                last_seen: at,
+                last_checked: at,
+                consecutive_failures: 0,
+                node: node.clone(),
+                checks: Vec::new(),
+            });
+
+        if country_code.is_some() {
+            record.country_code = country_code.map(String::from);
+        }
+        let in_registry = get_chain_by_id(chain.id).is_some_and(|c| !c.genesis_hash.is_empty());
+        record.chain = (!in_registry).then(|| chain.clone());
+        record.last_seen = at;
+        record.consecutive_failures = 0;
+        record.node = merge_details(&record.node, node);
+        record.push_check(CheckRecord {
+            at,
+            failure: None,
+            latency_ms: Some(node.latency_ms),
+            block_number: Some(node.block_number),
+        });
+    }
+
+    /// Record a failed validation. Nodes never seen healthy are not stored.
+    pub fn record_failure(&mut self, chain_id: u64, url: &str, error: &Error) {
+        let Some(record) = self.chains.get_mut(&chain_id).and_then(|nodes| nodes.get_mut(url)) else {
+            return;
+        };
+        record.consecutive_failures += 1;
+        record.push_check(CheckRecord {
+            at: now(),
+            failure: Some(error.reason().to_string()),
+            latency_ms: None,
+            block_number: None,
+        });
+    }
+
+    /// Refresh archive, capability and exposure data found after validation
+    /// without adding a check
+    pub fn update_details(&mut self, chain_id: u64, node: &ValidatedNode) {
+        if let Some(record) = self.chains.get_mut(&chain_id).and_then(|nodes| nodes.get_mut(&node.url)) {
+            record.node = merge_details(&record.node, node);
+        }
+    }
+
+    pub fn get(&self, chain_id: u64, url: &str) -> Option<&NodeRecord> {
+        self.chains.get(&chain_id)?.get(url)
+    }
+
+    pub fn records(&self, chain_id: u64) -> impl Iterator<Item = &NodeRecord> {
+        self.chains.get(&chain_id).into_iter().flat_map(|nodes| nodes.values())
+    }
+
+    pub fn all_records(&self) -> impl Iterator<Item = &NodeRecord> {
+        self.chains.values().flat_map(|nodes| nodes.values())
+    }
+
+    pub fn remove(&mut self, chain_id: u64, url: &str) -> Option<NodeRecord> {
+        self.chains.get_mut(&chain_id)?.remove(url)
+    }
+}
+
+/// Take the fresh validation results, keeping archive/capability/exposure
+/// data and host metadata from earlier searches when this one lacks them
+fn merge_details(previous: &ValidatedNode, fresh: &ValidatedNode) -> ValidatedNode {
+    let mut node = fresh.clone();
+    if node.oldest_state_block.is_none() {
+        node.oldest_state_block = previous.oldest_state_block;
+        node.is_archive = previous.is_archive;
+    }
+    if node.capabilities.is_empty() {
+        node.capabilities = previous.capabilities.clone();
+    }
+    if node.security.is_none() {
+        node.security = previous.security.clone();
+    }
+    if node.supports_subscriptions.is_none() {
+        node.supports_subscriptions = previous.supports_subscriptions;
+        node.header_delay_ms = previous.header_delay_ms;
+    }
+    if node.client.is_none() {
+        node.client = previous.client.clone();
+    }
+    if node.tls.is_none() {
+        node.tls = previous.tls.clone();
+    }
+    if node.host.is_none() {
+        node.host = previous.host.clone();
+    }
+    node
+}
@@ -0,0 +1,177 @@
+use super::{now, NodeDb, NodeRecord};
+use crate::config::Protocol;
+use crate::search::{NodeType, SearchRequest};
+use crate::validator::capabilities::supports_tracing;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+
+const NODE_DB_FILE: &str = "nodes.json";
+
+/// Node history persisted to `nodes.json` (or `NODE_DB_FILE`)
+#[derive(Debug, Clone)]
+pub struct NodeStore {
+    path: PathBuf,
+    db: Arc<RwLock<NodeDb>>,
+    /// Number of the latest snapshot taken, assigned under the database lock
+    version: Arc<AtomicU64>,
+    /// Number of the latest snapshot on disk, held while one is written so an
+    /// older snapshot never overwrites a newer one
+    writing: Arc<Mutex<u64>>,
+}
+
+impl NodeStore {
+    pub fn new() -> Self {
+        let path = env::var("NODE_DB_FILE")
+            .ok()
+            .filter(|p| !p.is_empty())
+            .unwrap_or_else(|| NODE_DB_FILE.to_string());
+        Self::open(Path::new(&path))
+    }
+
+    /// Load the store at `path`, starting empty when the file is missing or
+    /// invalid. An invalid file is moved aside to `<path>.bad` so the next
+    /// save does not overwrite the history it holds.
+    pub fn open(path: &Path) -> Self {
+        let db = match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                let bad = sibling(path, "bad");
+                match fs::rename(path, &bad) {
+                    Ok(()) => log::error!(
+                        "Failed to parse {}: {}, moved it to {} and starting empty",
+                        path.display(),
+                        e,
+                        bad.display()
+                    ),
+                    Err(rename_error) => log::error!(
+                        "Failed to parse {}: {}, starting empty (could not move it aside: {})",
+                        path.display(),
+                        e,
+                        rename_error
+                    ),
+                }
+                NodeDb::default()
+            }),
+            Err(_) => NodeDb::default(),
+        };
+
+        Self {
+            path: path.to_path_buf(),
+            db: Arc::new(RwLock::new(db)),
+            version: Arc::new(AtomicU64::new(0)),
+            writing: Arc::new(Mutex::new(0)),
+        }
+    }
+
+    /// Modify the database and write it back to disk. The file is written
+    /// after the database lock is released, so readers never wait on disk I/O.
+    /// Snapshots are numbered; one overtaken by a newer save is dropped.
+    pub async fn update<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut NodeDb) -> R,
+    {
+        let mut db = self.db.write().await;
+        let result = f(&mut db);
+        let content = serde_json::to_string_pretty(&*db);
+        let version = self.version.fetch_add(1, Ordering::SeqCst) + 1;
+        drop(db);
+
+        let mut written = self.writing.lock().await;
+        if version < *written {
+            return result;
+        }
+        match content {
+            Ok(content) => self.save(content).await,
+            Err(e) => log::error!("Failed to serialize {}: {}", self.path.display(), e),
+        }
+        *written = version;
+        result
+    }
+
+    pub async fn records(&self, chain_id: u64) -> Vec<NodeRecord> {
+        let db = self.db.read().await;
+        db.records(chain_id).cloned().collect()
+    }
+
+    pub async fn all_records(&self) -> Vec<NodeRecord> {
+        let db = self.db.read().await;
+        db.all_records().cloned().collect()
+    }
+
+    /// Healthy nodes checked within `max_age` seconds that match the request's
+    /// protocol, location, client and node type, fastest first
+    pub async fn known_good(&self, request: &SearchRequest, max_age: u64) -> Vec<NodeRecord> {
+        let config = &request.config;
+        let cutoff = now().saturating_sub(max_age);
+        let schemes: [&str; 2] = match config.protocol {
+            Protocol::Http => ["http://", "https://"],
+            Protocol::Ws => ["ws://", "wss://"],
+        };
+
+        let db = self.db.read().await;
+        let mut records: Vec<NodeRecord> = db
+            .records(request.chain.id)
+            .filter(|r| r.is_healthy() && r.last_checked >= cutoff)
+            .filter(|r| schemes.iter().any(|scheme| r.node.url.starts_with(scheme)))
+            .filter(|r| match &request.country_code {
+                Some(cc) => r.country_code.as_deref() == Some(cc.as_str()),
+                None => true,
+            })
+            .filter(|r| config.accepts_client(r.node.client.as_ref()))
+            .filter(|r| config.accepts_host(r.node.host.as_ref()))
+            .filter(|r| !(config.exclude_exposed && r.node.is_exposed()))
+            .filter(|r| !config.valid_tls_only || r.node.tls.as_ref().is_some_and(|t| t.valid))
+            .filter(|r| match request.node_type {
+                NodeType::Archive => r
+                    .node
+                    .oldest_state_block
+                    .is_some_and(|b| b <= request.chain.archive_bound(config.archive_from_block)),
+                NodeType::Trace => supports_tracing(&r.node.capabilities),
+                NodeType::Full | NodeType::Bulk => true,
+            })
+            .cloned()
+            .collect();
+
+        records.sort_by_key(|r| r.node.latency_ms);
+        records.truncate(request.node_type.target_count(config));
+        records
+    }
+
+    /// Write to `<path>.tmp` and rename it over the database, so a crash
+    /// mid-write leaves the previous file intact
+    async fn save(&self, content: String) {
+        let path = self.path.clone();
+        let written = tokio::task::spawn_blocking(move || {
+            let tmp = sibling(&path, "tmp");
+            let mut file = fs::File::create(&tmp)?;
+            file.write_all(content.as_bytes())?;
+            file.sync_all()?;
+            fs::rename(&tmp, &path)
+        })
+        .await;
+
+        match written {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => log::error!("Failed to write {}: {}", self.path.display(), e),
+            Err(e) => log::error!("Failed to write {}: {}", self.path.display(), e),
+        }
+    }
+}
+
+/// `path` with `.suffix` appended, e.g. `nodes.json.tmp`
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+impl Default for NodeStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,9 +1,11 @@
+use crate::chains::Chain;
+use crate::error::Error;
 use super::{RpcRequest, RpcResponse, ValidatedNode, ARCHIVE_TIMEOUT};
 use reqwest::Client;
-use std::time::Duration;
 use tokio::time::timeout;
 
-const ARCHIVE_BLOCKS: [&str; 3] = ["0x1", "0x64", "0xf4240"]; // 1, 100, 1000000
+/// Any address works for state probes; the zero address exists on every chain
+const STATE_PROBE_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
 
 pub struct ArchiveValidator {
     client: Client,
@@ -13,65 +15,114 @@ impl ArchiveValidator {
     pub fn new() -> Self {
         Self {
             client: Client::builder()
-                .timeout(Duration::from_secs(15)) // Higher timeout for archive queries
+                // Well inside the search budget, so a slow probe fails as a
+                // timeout instead of being cut off by `ARCHIVE_TIMEOUT`
+                .timeout(ARCHIVE_TIMEOUT / 2)
+                // HTTPS candidates may have invalid certificates, see HttpValidator
+                .danger_accept_invalid_certs(true)
                 .build()
                 .expect("Failed to create HTTP client"),
         }
     }
 
-    async fn rpc_call(&self, url: &str, request: &RpcRequest) -> Result<RpcResponse, String> {
-        let response = self
-            .client
-            .post(url)
-            .json(request)
-            .send()
-            .await
-            .map_err(|e| format!("HTTP error: {}", e))?;
+    async fn rpc_call(&self, url: &str, request: &RpcRequest) -> Result<RpcResponse, Error> {
+        let response = self.client.post(url).json(request).send().await?;
 
         if !response.status().is_success() {
-            return Err(format!("HTTP status: {}", response.status()));
+            return Err(Error::HttpStatus(response.status().as_u16()));
         }
 
-        response
-            .json()
-            .await
-            .map_err(|e| format!("JSON parse error: {}", e))
+        Ok(response.json().await?)
     }
 
-    /// Check if a node is an archive node by querying early blocks.
-    /// All three blocks (1, 100, 1000000) must return valid data.
-    pub async fn check_archive(&self, url: &str) -> Result<bool, String> {
-        let check = async {
-            for block in ARCHIVE_BLOCKS {
-                let request = RpcRequest::eth_get_block_by_number(block, false);
-                let response = self.rpc_call(url, &request).await?;
-
-                // Check if we got a valid block response (not null, not error)
-                match response.result {
-                    Some(value) if !value.is_null() => {
-                        // Verify the response has expected block fields
-                        if value.get("number").is_none() || value.get("hash").is_none() {
-                            return Err(format!("Block {} returned incomplete data", block));
-                        }
-                    }
-                    _ => {
-                        return Err(format!("Block {} not available", block));
-                    }
+    /// Whether the node can serve account state at `block`. Transport
+    /// failures are errors; RPC errors like "missing trie node" mean no state.
+    async fn has_state(&self, url: &str, block: u64) -> Result<bool, Error> {
+        let request = RpcRequest::eth_get_balance(STATE_PROBE_ADDRESS, &format!("0x{:x}", block));
+        let response = self.rpc_call(url, &request).await?;
+
+        Ok(response.error.is_none()
+            && response
+                .result
+                .as_ref()
+                .and_then(|v| v.as_str())
+                .is_some_and(|balance| balance.starts_with("0x")))
+    }
+
+    /// Find the oldest block the node can serve state for, searching between
+    /// `first_state_block` (at least block 1) and `head`. Nodes with state at
+    /// the lower bound report `first_state_block`, so full archive nodes of a
+    /// chain with state from genesis report 0.
+    /// `probe_heights` are checked first to narrow the search range.
+    /// If the time budget runs out mid-search, the oldest block confirmed so
+    /// far is returned, which may be newer than the true answer.
+    pub async fn find_oldest_state_block(
+        &self,
+        url: &str,
+        head: u64,
+        probe_heights: &[u64],
+        first_state_block: u64,
+    ) -> Result<u64, Error> {
+        let floor = first_state_block.max(1);
+        if head <= floor || !self.has_state(url, head).await? {
+            return Err(Error::ArchiveMissing { block: head });
+        }
+
+        let mut probes: Vec<u64> = probe_heights
+            .iter()
+            .copied()
+            .chain(std::iter::once(floor))
+            .filter(|&h| h >= floor && h < head)
+            .collect();
+        probes.sort_unstable();
+        probes.dedup();
+
+        let mut oldest_known = head;
+        let search = async {
+            // Invariant: block `lo` has no state, block `oldest_known` does
+            let mut lo = None;
+            for height in probes {
+                if self.has_state(url, height).await? {
+                    oldest_known = height;
+                    break;
                 }
+                lo = Some(height);
             }
-            Ok(true)
+
+            let Some(mut lo) = lo else {
+                // The lower bound has state
+                oldest_known = first_state_block;
+                return Ok::<(), Error>(());
+            };
+
+            while oldest_known - lo > 1 {
+                let mid = lo + (oldest_known - lo) / 2;
+                if self.has_state(url, mid).await? {
+                    oldest_known = mid;
+                } else {
+                    lo = mid;
+                }
+            }
+            Ok(())
         };
 
-        timeout(ARCHIVE_TIMEOUT, check)
-            .await
-            .map_err(|_| "Archive check timeout".to_string())?
+        match timeout(ARCHIVE_TIMEOUT, search).await {
+            Ok(Err(e)) => log::debug!("Archive search for {} stopped early: {}", url, e),
+            Err(_) => log::debug!("Archive search for {} timed out", url),
+            Ok(Ok(())) => {}
+        }
+
+        Ok(oldest_known)
     }
 
     /// Validate a node as an archive node.
-    /// Takes a pre-validated node and checks if it has archive capabilities.
-    pub async fn validate_archive(&self, mut node: ValidatedNode) -> Result<ValidatedNode, String> {
-        let is_archive = self.check_archive(&node.url).await?;
-        node.is_archive = is_archive;
+    /// Takes a pre-validated node and records how far back its state goes.
+    pub async fn validate_archive(&self, mut node: ValidatedNode, chain: &Chain) -> Result<ValidatedNode, Error> {
+        let oldest = self
+            .find_oldest_state_block(&node.url, node.block_number, &chain.archive_probe_heights, chain.first_state_block)
+            .await?;
+        node.oldest_state_block = Some(oldest);
+        node.is_archive = oldest <= chain.first_state_block;
         Ok(node)
     }
 }
@@ -0,0 +1,91 @@
+use super::{RpcRequest, RpcResponse};
+
+/// Methods probed by default, one or two per namespace we care about
+pub const DEFAULT_CAPABILITY_METHODS: [&str; 5] = [
+    "debug_traceTransaction",
+    "debug_traceBlockByNumber",
+    "trace_block",
+    "txpool_content",
+    "txpool_status",
+];
+
+/// JSON-RPC "invalid params": the method exists and rejected its arguments
+const INVALID_PARAMS: i64 = -32602;
+
+/// What a working tracer complains about when handed the genesis block or a
+/// zero hash
+const PROBE_SUBJECTS: [&str; 4] = ["transaction", "block", "genesis", "argument"];
+
+/// How a working tracer complains about the probe arguments
+const PROBE_FAILURES: [&str; 5] = [
+    "not found",
+    "not traceable",
+    "cannot find",
+    "unknown",
+    "invalid",
+];
+
+/// Words that point at the method or access policy rather than its arguments
+const POLICY_WORDS: [&str; 10] = [
+    "method",
+    "namespace",
+    "api",
+    "allowed",
+    "whitelist",
+    "unauthorized",
+    "forbidden",
+    "rate limit",
+    "too many",
+    "exceeded",
+];
+
+/// Build a cheap request for a capability method. Parameters point at the
+/// genesis block or a zero hash so an enabled method answers (or fails on
+/// the argument) without doing any real work.
+pub fn probe_request(method: &str) -> RpcRequest {
+    let zero_hash = format!("0x{}", "0".repeat(64));
+    let params = match method {
+        "debug_traceTransaction" | "trace_transaction" | "trace_replayTransaction" => {
+            vec![serde_json::json!(zero_hash)]
+        }
+        "debug_traceBlockByNumber" => vec![serde_json::json!("0x0"), serde_json::json!({})],
+        "debug_traceBlockByHash" => vec![serde_json::json!(zero_hash), serde_json::json!({})],
+        "trace_block" | "debug_getRawBlock" | "debug_getRawHeader" => vec![serde_json::json!("0x0")],
+        _ => vec![],
+    };
+    RpcRequest::new(method, params)
+}
+
+/// Whether a probe response shows the method is exposed. Only a result, or
+/// an error showing the method ran and rejected the probe arguments (e.g.
+/// "transaction not found" for the zero hash), counts as enabled. Anything
+/// else - "method not found", access-list rejections from a proxy, rate
+/// limits, gateway errors - does not.
+pub fn is_enabled(method: &str, response: &RpcResponse) -> bool {
+    match &response.error {
+        None => response.result.is_some(),
+        Some(err) => {
+            let message = err.message.to_lowercase();
+            let about_policy = message.contains(&method.to_lowercase())
+                || POLICY_WORDS.iter().any(|w| message.contains(w));
+            if about_policy {
+                return false;
+            }
+            err.code == INVALID_PARAMS
+                || (PROBE_SUBJECTS.iter().any(|s| message.contains(s))
+                    && PROBE_FAILURES.iter().any(|f| message.contains(f)))
+        }
+    }
+}
+
+/// Namespace prefix of a method, e.g. "debug" for "debug_traceTransaction"
+pub fn namespace(method: &str) -> &str {
+    method.split('_').next().unwrap_or(method)
+}
+
+/// Whether a set of enabled methods qualifies a node as a trace/debug node
+pub fn supports_tracing(capabilities: &[String]) -> bool {
+    capabilities
+        .iter()
+        .any(|m| matches!(namespace(m), "debug" | "trace"))
+}
@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+
+/// Execution client implementations we can recognise from `web3_clientVersion`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ClientFamily {
+    Geth,
+    Erigon,
+    Nethermind,
+    Besu,
+    Reth,
+    Bor,
+    Other,
+}
+
+impl ClientFamily {
+    /// Families users can filter on (everything but `Other`)
+    pub const KNOWN: [ClientFamily; 6] = [
+        ClientFamily::Geth,
+        ClientFamily::Erigon,
+        ClientFamily::Nethermind,
+        ClientFamily::Besu,
+        ClientFamily::Reth,
+        ClientFamily::Bor,
+    ];
+
+    pub fn from_name(name: &str) -> Self {
+        // Forks like bsc-geth and op-geth report themselves as "Geth"
+        match name.to_lowercase().as_str() {
+            "geth" | "bsc-geth" | "op-geth" => ClientFamily::Geth,
+            "erigon" => ClientFamily::Erigon,
+            "nethermind" => ClientFamily::Nethermind,
+            "besu" => ClientFamily::Besu,
+            "reth" | "op-reth" => ClientFamily::Reth,
+            "bor" => ClientFamily::Bor,
+            _ => ClientFamily::Other,
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ClientFamily::Geth => "Geth",
+            ClientFamily::Erigon => "Erigon",
+            ClientFamily::Nethermind => "Nethermind",
+            ClientFamily::Besu => "Besu",
+            ClientFamily::Reth => "Reth",
+            ClientFamily::Bor => "Bor",
+            ClientFamily::Other => "Other",
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ClientFamily::Geth => "geth",
+            ClientFamily::Erigon => "erigon",
+            ClientFamily::Nethermind => "nethermind",
+            ClientFamily::Besu => "besu",
+            ClientFamily::Reth => "reth",
+            ClientFamily::Bor => "bor",
+            ClientFamily::Other => "other",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::KNOWN.into_iter().find(|f| f.code() == code)
+    }
+}
+
+/// Structured form of a `web3_clientVersion` string such as
+/// `Geth/v1.13.5-stable-916d6a44/linux-amd64/go1.21.4`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ClientVersion {
+    pub family: ClientFamily,
+    pub name: String,
+    pub version: Option<String>,
+    pub os: Option<String>,
+    /// Language runtime the client was built with (e.g. `go1.21.4`, `dotnet8.0.2`)
+    pub runtime: Option<String>,
+    pub raw: String,
+}
+
+impl ClientVersion {
+    pub fn parse(raw: &str) -> Self {
+        let raw = raw.trim();
+        let parts: Vec<&str> = raw.split('/').map(str::trim).collect();
+        let name = parts.first().copied().unwrap_or_default().to_string();
+
+        // Some clients insert an identity segment (Geth/my-node/v1.13.5/...),
+        // so look for the version instead of relying on its position
+        let version_idx = parts
+            .iter()
+            .skip(1)
+            .position(|p| looks_like_version(p))
+            .map(|i| i + 1);
+
+        let (version, rest) = match version_idx {
+            Some(i) => (Some(parts[i].to_string()), &parts[i + 1..]),
+            None => (None, parts.get(1..).unwrap_or_default()),
+        };
+
+        let mut os = None;
+        let mut runtime = None;
+        for part in rest {
+            if is_runtime(part) {
+                runtime = Some(part.to_string());
+            } else if os.is_none() && !part.is_empty() {
+                os = Some(part.to_string());
+            }
+        }
+
+        Self {
+            family: ClientFamily::from_name(&name),
+            name,
+            version,
+            os,
+            runtime,
+            raw: raw.to_string(),
+        }
+    }
+
+    /// Short label for result listings, e.g. "Geth v1.13.5"
+    pub fn label(&self) -> String {
+        let name = match self.family {
+            ClientFamily::Other => self.name.as_str(),
+            family => family.display_name(),
+        };
+        match &self.version {
+            Some(version) => {
+                // Drop build metadata like "-stable-916d6a44" or "+20b10b35"
+                let short = version.split(['-', '+']).next().unwrap_or(version);
+                format!("{} {}", name, short)
+            }
+            None => name.to_string(),
+        }
+    }
+}
+
+fn looks_like_version(part: &str) -> bool {
+    let part = part.strip_prefix('v').unwrap_or(part);
+    part.chars().next().is_some_and(|c| c.is_ascii_digit()) && part.contains('.')
+}
+
+fn is_runtime(part: &str) -> bool {
+    let lower = part.to_lowercase();
+    ["go1", "rustc", "dotnet", "openjdk", "java"]
+        .iter()
+        .any(|prefix| lower.starts_with(prefix))
+}
@@ -1,38 +1,79 @@
-use super::{parse_hex_u64, RpcRequest, RpcResponse, ValidatedNode, HTTP_TIMEOUT};
+use crate::error::Error;
+use super::capabilities;
+use super::client::ClientVersion;
+use super::security::{SecurityAudit, AUDIT_METHODS};
+use super::{Checkpoint, RpcRequest, RpcResponse, ValidatedNode, HTTP_TIMEOUT};
+use futures::future::join_all;
 use reqwest::Client;
 use std::time::Instant;
 
 pub struct HttpValidator {
+    /// Used for candidate nodes; accepts any certificate so HTTPS nodes with
+    /// self-signed or mismatched certificates can still be validated
     client: Client,
+    /// Verifies certificates; used for reference RPCs and certificate checks
+    strict_client: Client,
 }
 
 impl HttpValidator {
     pub fn new() -> Self {
         Self {
             client: Client::builder()
+                .timeout(HTTP_TIMEOUT)
+                .danger_accept_invalid_certs(true)
+                .build()
+                .expect("Failed to create HTTP client"),
+            strict_client: Client::builder()
                 .timeout(HTTP_TIMEOUT)
                 .build()
                 .expect("Failed to create HTTP client"),
         }
     }
 
-    async fn rpc_call(&self, url: &str, request: &RpcRequest) -> Result<RpcResponse, String> {
-        let response = self
-            .client
-            .post(url)
-            .json(request)
-            .send()
-            .await
-            .map_err(|e| format!("HTTP error: {}", e))?;
+    async fn rpc_call(&self, url: &str, request: &RpcRequest) -> Result<RpcResponse, Error> {
+        Self::send(&self.client, url, request).await
+    }
+
+    async fn strict_rpc_call(&self, url: &str, request: &RpcRequest) -> Result<RpcResponse, Error> {
+        Self::send(&self.strict_client, url, request).await
+    }
+
+    async fn send(client: &Client, url: &str, request: &RpcRequest) -> Result<RpcResponse, Error> {
+        let response = client.post(url).json(request).send().await?;
 
         if !response.status().is_success() {
-            return Err(format!("HTTP status: {}", response.status()));
+            return Err(Error::HttpStatus(response.status().as_u16()));
         }
 
-        response
-            .json()
+        Ok(response.json().await?)
+    }
+
+    /// Try each candidate URL with `eth_chainId` and return the first, in
+    /// the given order, that answers
+    pub async fn find_endpoint(&self, urls: &[String]) -> Result<String, Error> {
+        let probes = urls
+            .iter()
+            .map(|url| async move { self.rpc_call(url, &RpcRequest::eth_chain_id()).await?.into_u64() });
+
+        let results = join_all(probes).await;
+        let mut first_error = None;
+        for (url, result) in urls.iter().zip(results) {
+            match result {
+                Ok(_) => return Ok(url.clone()),
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        Err(first_error.unwrap_or_else(|| Error::Connect("no HTTP endpoint to try".to_string())))
+    }
+
+    /// Whether the endpoint answers a client that verifies its certificate
+    pub async fn has_valid_certificate(&self, url: &str) -> bool {
+        self.strict_rpc_call(url, &RpcRequest::eth_chain_id())
             .await
-            .map_err(|e| format!("JSON parse error: {}", e))
+            .and_then(|r| r.into_u64())
+            .is_ok()
     }
 
     pub async fn validate(
@@ -42,44 +83,32 @@ impl HttpValidator {
         expected_genesis_hash: &str,
         reference_block: u64,
         sync_tolerance: u64,
-    ) -> Result<ValidatedNode, String> {
+        checkpoint: Option<&Checkpoint>,
+    ) -> Result<ValidatedNode, Error> {
         let start = Instant::now();
 
         // Check chain ID
-        let chain_id_resp = self.rpc_call(url, &RpcRequest::eth_chain_id()).await?;
-        let chain_id_hex = chain_id_resp
-            .result
-            .and_then(|v| v.as_str().map(String::from))
-            .ok_or("No chain ID in response")?;
-        let chain_id = parse_hex_u64(&chain_id_hex).ok_or("Invalid chain ID format")?;
+        let chain_id = self.rpc_call(url, &RpcRequest::eth_chain_id()).await?.into_u64()?;
 
         if chain_id != expected_chain_id {
-            return Err(format!(
-                "Chain ID mismatch: expected {}, got {}",
-                expected_chain_id, chain_id
-            ));
+            return Err(Error::ChainIdMismatch {
+                expected: expected_chain_id,
+                actual: chain_id,
+            });
         }
 
         // Check genesis block hash
-        let genesis_resp = self
+        let genesis_hash = self
             .rpc_call(url, &RpcRequest::eth_get_block_by_number("0x0", false))
-            .await?;
-        let genesis_hash = genesis_resp
-            .result
-            .and_then(|v| v.get("hash").and_then(|h| h.as_str()).map(String::from))
-            .ok_or("No genesis hash in response")?;
-
-        if genesis_hash.to_lowercase() != expected_genesis_hash.to_lowercase() {
-            return Err("Genesis hash mismatch - possible honeypot".to_string());
+            .await?
+            .into_block_hash()?;
+
+        if genesis_hash != expected_genesis_hash.to_lowercase() {
+            return Err(Error::GenesisMismatch);
         }
 
         // Check sync status
-        let block_resp = self.rpc_call(url, &RpcRequest::eth_block_number()).await?;
-        let block_hex = block_resp
-            .result
-            .and_then(|v| v.as_str().map(String::from))
-            .ok_or("No block number in response")?;
-        let block_number = parse_hex_u64(&block_hex).ok_or("Invalid block number format")?;
+        let block_number = self.rpc_call(url, &RpcRequest::eth_block_number()).await?.into_u64()?;
 
         let block_diff = if reference_block > block_number {
             reference_block - block_number
@@ -88,29 +117,97 @@ impl HttpValidator {
         };
 
         if block_diff > sync_tolerance {
-            return Err(format!(
-                "Node not synced: {} blocks behind (tolerance: {})",
-                block_diff, sync_tolerance
-            ));
+            return Err(Error::NotSynced {
+                behind: block_diff,
+                tolerance: sync_tolerance,
+            });
+        }
+
+        // Check the node is on the same chain history as the reference
+        if let Some(checkpoint) = checkpoint.filter(|c| c.block <= block_number) {
+            let hash = self
+                .rpc_call(url, &RpcRequest::eth_get_block_by_number(&format!("0x{:x}", checkpoint.block), false))
+                .await?
+                .into_block_hash()?;
+
+            if hash != checkpoint.hash {
+                return Err(Error::ForkMismatch { block: checkpoint.block });
+            }
         }
 
         let latency_ms = start.elapsed().as_millis() as u64;
 
+        // Fingerprint client software (best effort, not part of validation)
+        let client = self
+            .rpc_call(url, &RpcRequest::web3_client_version())
+            .await
+            .ok()
+            .and_then(|r| r.result)
+            .and_then(|v| v.as_str().map(ClientVersion::parse));
+
         Ok(ValidatedNode {
             url: url.to_string(),
             latency_ms,
             block_number,
             is_archive: false,
+            oldest_state_block: None,
+            client,
+            capabilities: Vec::new(),
+            security: None,
+            supports_subscriptions: None,
+            header_delay_ms: None,
+            tls: None,
+            host: None,
         })
     }
 
-    pub async fn get_current_block(&self, rpc_url: &str) -> Result<u64, String> {
-        let resp = self.rpc_call(rpc_url, &RpcRequest::eth_block_number()).await?;
-        let block_hex = resp
-            .result
-            .and_then(|v| v.as_str().map(String::from))
-            .ok_or("No block number in response")?;
-        parse_hex_u64(&block_hex).ok_or("Invalid block number format".to_string())
+    /// Probe which of `methods` the node exposes and record them on the node
+    pub async fn probe_capabilities(&self, mut node: ValidatedNode, methods: &[String]) -> ValidatedNode {
+        let url = node.url.as_str();
+        let probes = methods.iter().map(|method| async move {
+            let response = self.rpc_call(url, &capabilities::probe_request(method)).await.ok()?;
+            capabilities::is_enabled(method, &response).then(|| method.clone())
+        });
+
+        let enabled = join_all(probes).await.into_iter().flatten().collect();
+        node.capabilities = enabled;
+        node
+    }
+
+    /// Check the node for unlocked accounts and admin/personal/miner namespaces
+    pub async fn audit_security(&self, mut node: ValidatedNode) -> ValidatedNode {
+        let url = node.url.as_str();
+        let audits = AUDIT_METHODS.iter().map(|&method| async move {
+            let response = self.rpc_call(url, &SecurityAudit::request(method)).await.ok()?;
+            Some((method, response))
+        });
+
+        let mut audit = SecurityAudit::default();
+        for (method, response) in join_all(audits).await.into_iter().flatten() {
+            audit.record(method, &response);
+        }
+        node.security = Some(audit);
+        node
+    }
+
+    pub async fn get_chain_id(&self, rpc_url: &str) -> Result<u64, Error> {
+        self.strict_rpc_call(rpc_url, &RpcRequest::eth_chain_id()).await?.into_u64()
+    }
+
+    pub async fn get_genesis_hash(&self, rpc_url: &str) -> Result<String, Error> {
+        self.strict_rpc_call(rpc_url, &RpcRequest::eth_get_block_by_number("0x0", false))
+            .await?
+            .into_block_hash()
+    }
+
+    pub async fn get_block_hash(&self, rpc_url: &str, block: u64) -> Result<String, Error> {
+        self.strict_rpc_call(rpc_url, &RpcRequest::eth_get_block_by_number(&format!("0x{:x}", block), false))
+            .await?
+            .into_block_hash()
+    }
+
+    pub async fn get_current_block(&self, rpc_url: &str) -> Result<u64, Error> {
+        self.strict_rpc_call(rpc_url, &RpcRequest::eth_block_number()).await?.into_u64()
     }
 }
 
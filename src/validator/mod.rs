@@ -1,7 +1,17 @@
 pub mod http;
 pub mod ws;
 pub mod archive;
+pub mod capabilities;
+pub mod client;
+pub mod security;
+pub mod session;
+pub mod tls;
 
+use crate::discovery::hosting::HostInfo;
+use crate::error::Error;
+use client::ClientVersion;
+use security::SecurityAudit;
+use tls::TlsInfo;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
@@ -35,6 +45,25 @@ impl RpcRequest {
         Self::new("eth_blockNumber", vec![])
     }
 
+    pub fn eth_get_balance(address: &str, block: &str) -> Self {
+        Self::new(
+            "eth_getBalance",
+            vec![serde_json::json!(address), serde_json::json!(block)],
+        )
+    }
+
+    pub fn web3_client_version() -> Self {
+        Self::new("web3_clientVersion", vec![])
+    }
+
+    pub fn eth_subscribe(kind: &str) -> Self {
+        Self::new("eth_subscribe", vec![serde_json::json!(kind)])
+    }
+
+    pub fn eth_unsubscribe(subscription_id: &str) -> Self {
+        Self::new("eth_unsubscribe", vec![serde_json::json!(subscription_id)])
+    }
+
     pub fn eth_get_block_by_number(block: &str, full_tx: bool) -> Self {
         Self::new(
             "eth_getBlockByNumber",
@@ -43,24 +72,109 @@ impl RpcRequest {
     }
 }
 
+/// Block hash agreed on by the reference RPCs, compared against candidates
+/// to catch nodes on a minority fork or replaying a fake chain
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    pub block: u64,
+    /// Lowercased block hash
+    pub hash: String,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct RpcResponse {
+    /// Echo of the request id, used to correlate pipelined WebSocket requests
+    #[serde(default)]
+    pub id: Option<serde_json::Value>,
     pub result: Option<serde_json::Value>,
     pub error: Option<RpcError>,
 }
 
+impl RpcResponse {
+    /// The result value, or the node's JSON-RPC error
+    pub fn into_result(self) -> Result<serde_json::Value, Error> {
+        if let Some(err) = self.error {
+            return Err(Error::RpcError {
+                code: err.code,
+                message: err.message,
+            });
+        }
+        match self.result {
+            Some(value) if !value.is_null() => Ok(value),
+            _ => Err(Error::InvalidResponse("empty result".to_string())),
+        }
+    }
+
+    /// Result as a hex quantity such as a chain ID or block number
+    pub fn into_u64(self) -> Result<u64, Error> {
+        let value = self.into_result()?;
+        value
+            .as_str()
+            .and_then(parse_hex_u64)
+            .ok_or_else(|| Error::InvalidResponse(format!("expected hex quantity, got {}", value)))
+    }
+
+    /// Lowercased `hash` of a block object result
+    pub fn into_block_hash(self) -> Result<String, Error> {
+        let value = self.into_result()?;
+        value
+            .get("hash")
+            .and_then(|h| h.as_str())
+            .map(|h| h.to_lowercase())
+            .ok_or_else(|| Error::InvalidResponse("block without hash".to_string()))
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct RpcError {
     pub code: i64,
     pub message: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidatedNode {
     pub url: String,
     pub latency_ms: u64,
     pub block_number: u64,
     pub is_archive: bool,
+    /// Oldest block with retrievable state, `None` until the archive check runs
+    pub oldest_state_block: Option<u64>,
+    pub client: Option<ClientVersion>,
+    /// Probed RPC methods the node answers (see `capabilities`)
+    pub capabilities: Vec<String>,
+    /// Exposure audit result, `None` until `audit_security` has run
+    pub security: Option<SecurityAudit>,
+    /// Whether `eth_subscribe("newHeads")` streamed a header, `None` until
+    /// the WebSocket subscription check has run
+    #[serde(default)]
+    pub supports_subscriptions: Option<bool>,
+    /// Time between the first streamed header's timestamp and its arrival
+    #[serde(default)]
+    pub header_delay_ms: Option<u64>,
+    /// Certificate details, `None` for plain `http://` and `ws://` endpoints
+    #[serde(default)]
+    pub tls: Option<TlsInfo>,
+    /// Org, ASN, location and scan details from the discovery source
+    #[serde(default)]
+    pub host: Option<HostInfo>,
+}
+
+impl ValidatedNode {
+    /// Distinct namespaces among the enabled capability methods
+    pub fn namespaces(&self) -> Vec<&str> {
+        let mut namespaces: Vec<&str> = self
+            .capabilities
+            .iter()
+            .map(|m| capabilities::namespace(m))
+            .collect();
+        namespaces.sort_unstable();
+        namespaces.dedup();
+        namespaces
+    }
+
+    pub fn is_exposed(&self) -> bool {
+        self.security.as_ref().is_some_and(|s| s.is_exposed())
+    }
 }
 
 pub fn parse_hex_u64(s: &str) -> Option<u64> {
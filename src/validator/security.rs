@@ -0,0 +1,114 @@
+use super::{RpcRequest, RpcResponse};
+use serde::{Deserialize, Serialize};
+
+/// Method used to detect a served `miner` namespace. It is called without
+/// its required argument, so an exposed node rejects the arguments and
+/// nothing is changed.
+pub const MINER_PROBE: &str = "miner_setExtra";
+
+/// JSON-RPC "invalid params", the answer of a served `MINER_PROBE`
+const INVALID_PARAMS: i64 = -32602;
+
+/// Methods used to audit a node. Each is read-only or, for `MINER_PROBE`,
+/// fails on its arguments, so auditing a misconfigured node never makes
+/// things worse.
+pub const AUDIT_METHODS: [&str; 6] = [
+    "eth_accounts",
+    "personal_listAccounts",
+    "personal_listWallets",
+    "admin_nodeInfo",
+    MINER_PROBE,
+    "rpc_modules",
+];
+
+/// Dangerous exposure found on a node
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SecurityAudit {
+    /// Accounts listed by `eth_accounts`/`personal_listAccounts`. Locked
+    /// keystores are harmless, so this alone is not an exposure.
+    pub listed_accounts: usize,
+    /// Accounts in wallets `personal_listWallets` reports as `Unlocked`.
+    /// A public node with an unlocked account is either misconfigured or a
+    /// signing trap.
+    pub unlocked_accounts: usize,
+    pub personal: bool,
+    pub admin: bool,
+    pub miner: bool,
+}
+
+impl SecurityAudit {
+    pub fn request(method: &str) -> RpcRequest {
+        RpcRequest::new(method, vec![])
+    }
+
+    /// Fold one audit response into the report
+    pub fn record(&mut self, method: &str, response: &RpcResponse) {
+        if method == MINER_PROBE {
+            // Only a served method gets as far as checking its arguments;
+            // "not allowed", rate limits and the like say nothing
+            self.miner |= response.error.as_ref().is_some_and(|err| {
+                err.code == INVALID_PARAMS && err.message.to_lowercase().contains("argument")
+            });
+            return;
+        }
+        if response.error.is_some() {
+            return;
+        }
+        let Some(result) = &response.result else {
+            return;
+        };
+
+        match method {
+            "eth_accounts" | "personal_listAccounts" => {
+                self.personal |= method == "personal_listAccounts";
+                let accounts = result.as_array().map(Vec::as_slice).unwrap_or_default();
+                self.listed_accounts = self.listed_accounts.max(accounts.len());
+            }
+            "personal_listWallets" => {
+                self.personal = true;
+                let unlocked: usize = result
+                    .as_array()
+                    .map(Vec::as_slice)
+                    .unwrap_or_default()
+                    .iter()
+                    .filter(|wallet| wallet["status"].as_str().is_some_and(|s| s.eq_ignore_ascii_case("unlocked")))
+                    .map(|wallet| wallet["accounts"].as_array().map_or(1, |a| a.len().max(1)))
+                    .sum();
+                self.unlocked_accounts = self.unlocked_accounts.max(unlocked);
+            }
+            "admin_nodeInfo" => self.admin = true,
+            "rpc_modules" => {
+                // Geth-style clients list every namespace served on this endpoint
+                if let Some(modules) = result.as_object() {
+                    self.admin |= modules.contains_key("admin");
+                    self.personal |= modules.contains_key("personal");
+                    self.miner |= modules.contains_key("miner");
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn is_exposed(&self) -> bool {
+        !self.flags().is_empty()
+    }
+
+    /// Short labels for every exposure found
+    pub fn flags(&self) -> Vec<&'static str> {
+        let mut flags = Vec::new();
+        if self.unlocked_accounts > 0 {
+            flags.push("unlocked accounts");
+        }
+        if self.personal {
+            flags.push("personal");
+        }
+        if self.admin {
+            flags.push("admin");
+        }
+        if self.miner {
+            flags.push("miner");
+        }
+        flags
+    }
+}
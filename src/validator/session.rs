@@ -0,0 +1,177 @@
+use crate::error::Error;
+use super::{RpcRequest, RpcResponse, HTTP_TIMEOUT};
+use futures::{SinkExt, StreamExt};
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::time::{timeout_at, Instant};
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+/// Subscription notifications kept while waiting for responses; older ones are dropped
+const MAX_BUFFERED_NOTIFICATIONS: usize = 64;
+
+/// One WebSocket connection to a node, reused for every request made while
+/// validating it. Responses are matched to requests by JSON-RPC id, so
+/// requests can be pipelined and interleaved subscription messages skipped.
+pub struct WsSession {
+    stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    next_id: u64,
+    /// `eth_subscription` notifications received while waiting for responses
+    notifications: VecDeque<serde_json::Value>,
+}
+
+impl WsSession {
+    pub async fn connect(url: &str) -> Result<Self, Error> {
+        let (stream, _) = tokio::time::timeout(HTTP_TIMEOUT, connect_async(url))
+            .await
+            .map_err(|_| Error::Timeout)?
+            .map_err(|e| Error::Connect(e.to_string()))?;
+
+        Ok(Self {
+            stream,
+            next_id: 1,
+            notifications: VecDeque::new(),
+        })
+    }
+
+    /// Send one request and wait for its response
+    pub async fn call(&mut self, request: &RpcRequest) -> Result<RpcResponse, Error> {
+        self.call_many(std::slice::from_ref(request))
+            .await
+            .pop()
+            .unwrap_or_else(|| Err(Error::WebSocket("no response".to_string())))
+    }
+
+    /// Send all requests at once, then collect their responses in request
+    /// order. Requests left unanswered when the timeout expires fail with
+    /// `Error::Timeout`.
+    pub async fn call_many(&mut self, requests: &[RpcRequest]) -> Vec<Result<RpcResponse, Error>> {
+        let mut pending: HashMap<u64, usize> = HashMap::new();
+        let mut results: Vec<Option<Result<RpcResponse, Error>>> = vec![None; requests.len()];
+
+        for (index, request) in requests.iter().enumerate() {
+            let mut request = request.clone();
+            request.id = self.next_id;
+            self.next_id += 1;
+
+            let sent = match serde_json::to_string(&request) {
+                Ok(json) => self
+                    .stream
+                    .send(Message::Text(json))
+                    .await
+                    .map_err(|e| Error::WebSocket(format!("send error: {}", e))),
+                Err(e) => Err(Error::InvalidResponse(format!("JSON serialize error: {}", e))),
+            };
+            match sent {
+                Ok(()) => {
+                    pending.insert(request.id, index);
+                }
+                Err(e) => results[index] = Some(Err(e)),
+            }
+        }
+
+        let deadline = Instant::now() + HTTP_TIMEOUT;
+        while !pending.is_empty() {
+            let value = match self.next_value(deadline).await {
+                Ok(value) => value,
+                Err(e) => {
+                    for (_, index) in pending.drain() {
+                        results[index] = Some(Err(e.clone()));
+                    }
+                    break;
+                }
+            };
+
+            // Batch-style arrays are not sent by us, but tolerate them
+            let values = match value {
+                serde_json::Value::Array(values) => values,
+                value => vec![value],
+            };
+            for value in values {
+                self.dispatch(value, &mut pending, &mut results);
+            }
+        }
+
+        results
+            .into_iter()
+            .map(|r| r.unwrap_or(Err(Error::Timeout)))
+            .collect()
+    }
+
+    /// Route an incoming message to its pending request, or buffer it if it
+    /// is a subscription notification
+    fn dispatch(
+        &mut self,
+        value: serde_json::Value,
+        pending: &mut HashMap<u64, usize>,
+        results: &mut [Option<Result<RpcResponse, Error>>],
+    ) {
+        if value.get("method").and_then(|m| m.as_str()) == Some("eth_subscription") {
+            if self.notifications.len() >= MAX_BUFFERED_NOTIFICATIONS {
+                self.notifications.pop_front();
+            }
+            self.notifications.push_back(value);
+            return;
+        }
+
+        let Some(index) = value
+            .get("id")
+            .and_then(|id| id.as_u64())
+            .and_then(|id| pending.remove(&id))
+        else {
+            log::debug!("Ignoring uncorrelated WS message: {}", value);
+            return;
+        };
+
+        results[index] = Some(
+            serde_json::from_value(value)
+                .map_err(|e| Error::InvalidResponse(format!("JSON parse error: {}", e))),
+        );
+    }
+
+    /// Next JSON message from the node, skipping control frames
+    async fn next_value(&mut self, deadline: Instant) -> Result<serde_json::Value, Error> {
+        loop {
+            let message = timeout_at(deadline, self.stream.next())
+                .await
+                .map_err(|_| Error::Timeout)?
+                .ok_or_else(|| Error::WebSocket("connection closed without response".to_string()))?
+                .map_err(|e| Error::WebSocket(format!("receive error: {}", e)))?;
+
+            let text = match message {
+                Message::Text(text) => text,
+                Message::Binary(bytes) => String::from_utf8(bytes)
+                    .map_err(|_| Error::InvalidResponse("non-UTF-8 binary WS message".to_string()))?,
+                Message::Close(_) => {
+                    return Err(Error::WebSocket("connection closed without response".to_string()))
+                }
+                Message::Ping(_) | Message::Pong(_) | Message::Frame(_) => continue,
+            };
+
+            return serde_json::from_str(&text)
+                .map_err(|e| Error::InvalidResponse(format!("JSON parse error: {}", e)));
+        }
+    }
+
+    /// Next `eth_subscription` notification, buffered or read from the
+    /// connection, waiting at most `wait`
+    pub async fn next_notification(&mut self, wait: Duration) -> Result<serde_json::Value, Error> {
+        if let Some(notification) = self.notifications.pop_front() {
+            return Ok(notification);
+        }
+
+        let deadline = Instant::now() + wait;
+        loop {
+            let value = self.next_value(deadline).await?;
+            if value.get("method").and_then(|m| m.as_str()) == Some("eth_subscription") {
+                return Ok(value);
+            }
+            log::debug!("Ignoring uncorrelated WS message: {}", value);
+        }
+    }
+
+    /// Send a close frame, ignoring errors from nodes that already hung up
+    pub async fn close(mut self) {
+        let _ = tokio::time::timeout(HTTP_TIMEOUT, self.stream.close(None)).await;
+    }
+}
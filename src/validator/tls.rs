@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+/// Certificate of an HTTPS endpoint
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TlsInfo {
+    /// Whether a client that verifies certificates (as browsers do) can talk
+    /// to the endpoint
+    pub valid: bool,
+    /// Unix time the certificate expires, from the discovery source's scan
+    pub expires_at: Option<u64>,
+    /// Issuer organization or common name, from the discovery source's scan
+    pub issuer: Option<String>,
+}
+
+impl TlsInfo {
+    /// Seconds until the certificate expires, zero once it has
+    pub fn expires_in(&self, now: u64) -> Option<u64> {
+        self.expires_at.map(|at| at.saturating_sub(now))
+    }
+}
+
+/// Parse an X.509 time as Shodan reports it (`20250315235959Z`) into Unix time
+pub fn parse_cert_time(s: &str) -> Option<u64> {
+    let digits = s.strip_suffix('Z')?;
+    if digits.len() != 14 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let field = |range: std::ops::Range<usize>| digits[range].parse::<u64>().ok();
+    let (year, month, day) = (field(0..4)?, field(4..6)?, field(6..8)?);
+    let (hour, minute, second) = (field(8..10)?, field(10..12)?, field(12..14)?);
+    if year < 1970 || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    Some(days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60 + second)
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date (Howard Hinnant's algorithm)
+fn days_from_civil(year: u64, month: u64, day: u64) -> u64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
@@ -1,10 +1,16 @@
-use super::{parse_hex_u64, RpcRequest, RpcResponse, ValidatedNode, HTTP_TIMEOUT, WS_SEMAPHORE_LIMIT};
-use futures::{SinkExt, StreamExt};
+use crate::error::Error;
+use super::capabilities;
+use super::client::ClientVersion;
+use super::security::{SecurityAudit, AUDIT_METHODS};
+use super::session::WsSession;
+use super::{parse_hex_u64, Checkpoint, RpcRequest, RpcResponse, ValidatedNode, HTTP_TIMEOUT, WS_SEMAPHORE_LIMIT};
+use futures::stream::{self, StreamExt};
 use std::sync::Arc;
-use std::time::Instant;
-use tokio::sync::Semaphore;
-use tokio::time::timeout;
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// Candidate WebSocket endpoints of one host probed at the same time
+const ENDPOINT_PROBE_CONCURRENCY: usize = 2;
 
 pub struct WsValidator {
     semaphore: Arc<Semaphore>,
@@ -17,38 +23,47 @@ impl WsValidator {
         }
     }
 
-    async fn rpc_call(&self, url: &str, request: &RpcRequest) -> Result<RpcResponse, String> {
-        let _permit = self
+    /// Open a session to the node; the permit must be held for the session's lifetime
+    async fn open_session(&self, url: &str) -> Result<(SemaphorePermit<'_>, WsSession), Error> {
+        let permit = self
             .semaphore
             .acquire()
             .await
-            .map_err(|_| "Semaphore error")?;
-
-        let (mut ws_stream, _) = timeout(HTTP_TIMEOUT, connect_async(url))
-            .await
-            .map_err(|_| "WS connection timeout")?
-            .map_err(|e| format!("WS connection error: {}", e))?;
-
-        let request_json =
-            serde_json::to_string(request).map_err(|e| format!("JSON serialize error: {}", e))?;
-
-        ws_stream
-            .send(Message::Text(request_json))
-            .await
-            .map_err(|e| format!("WS send error: {}", e))?;
+            .map_err(|_| Error::WebSocket("semaphore closed".to_string()))?;
 
-        let response = timeout(HTTP_TIMEOUT, ws_stream.next())
-            .await
-            .map_err(|_| "WS response timeout")?
-            .ok_or("No WS response")?
-            .map_err(|e| format!("WS receive error: {}", e))?;
+        let session = WsSession::connect(url).await?;
+        Ok((permit, session))
+    }
 
-        let response_text = match response {
-            Message::Text(t) => t,
-            _ => return Err("Unexpected WS message type".to_string()),
-        };
+    /// Try each candidate URL with `eth_chainId` and return the first, in
+    /// the given order, that answers over WebSocket. At most
+    /// `ENDPOINT_PROBE_CONCURRENCY` connections are open at once, and probing
+    /// stops at the first endpoint that answers.
+    pub async fn find_endpoint(&self, urls: &[String]) -> Result<String, Error> {
+        let probes: Vec<_> = urls
+            .iter()
+            .map(|url| async move {
+                let result = self
+                    .call_all(url, &[RpcRequest::eth_chain_id()])
+                    .await
+                    .pop()
+                    .unwrap_or(Err(Error::Timeout))
+                    .and_then(|r| r.into_u64());
+                (url, result)
+            })
+            .collect();
+        let mut probes = stream::iter(probes).buffered(ENDPOINT_PROBE_CONCURRENCY);
 
-        serde_json::from_str(&response_text).map_err(|e| format!("JSON parse error: {}", e))
+        let mut first_error = None;
+        while let Some((url, result)) = probes.next().await {
+            match result {
+                Ok(_) => return Ok(url.clone()),
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        Err(first_error.unwrap_or_else(|| Error::Connect("no WebSocket endpoint to try".to_string())))
     }
 
     pub async fn validate(
@@ -58,66 +73,224 @@ impl WsValidator {
         expected_genesis_hash: &str,
         reference_block: u64,
         sync_tolerance: u64,
-    ) -> Result<ValidatedNode, String> {
-        let start = Instant::now();
-
-        // Check chain ID
-        let chain_id_resp = self.rpc_call(url, &RpcRequest::eth_chain_id()).await?;
-        let chain_id_hex = chain_id_resp
-            .result
-            .and_then(|v| v.as_str().map(String::from))
-            .ok_or("No chain ID in response")?;
-        let chain_id = parse_hex_u64(&chain_id_hex).ok_or("Invalid chain ID format")?;
-
-        if chain_id != expected_chain_id {
-            return Err(format!(
-                "Chain ID mismatch: expected {}, got {}",
-                expected_chain_id, chain_id
-            ));
-        }
+        checkpoint: Option<&Checkpoint>,
+    ) -> Result<ValidatedNode, Error> {
+        let (_permit, mut session) = self.open_session(url).await?;
+        let result = async {
+            let session = &mut session;
+            let start = Instant::now();
+
+            // Chain ID, genesis block and head are requested together over one connection
+            let mut responses = session
+                .call_many(&[
+                    RpcRequest::eth_chain_id(),
+                    RpcRequest::eth_get_block_by_number("0x0", false),
+                    RpcRequest::eth_block_number(),
+                ])
+                .await
+                .into_iter();
+            let mut next_response = || {
+                responses
+                    .next()
+                    .unwrap_or_else(|| Err(Error::WebSocket("missing response".to_string())))
+            };
+
+            // Check chain ID
+            let chain_id = next_response()?.into_u64()?;
+
+            if chain_id != expected_chain_id {
+                return Err(Error::ChainIdMismatch {
+                    expected: expected_chain_id,
+                    actual: chain_id,
+                });
+            }
+
+            // Check genesis block hash
+            let genesis_hash = next_response()?.into_block_hash()?;
+
+            if genesis_hash != expected_genesis_hash.to_lowercase() {
+                return Err(Error::GenesisMismatch);
+            }
+
+            // Check sync status
+            let block_number = next_response()?.into_u64()?;
+
+            let block_diff = if reference_block > block_number {
+                reference_block - block_number
+            } else {
+                block_number - reference_block
+            };
+
+            if block_diff > sync_tolerance {
+                return Err(Error::NotSynced {
+                    behind: block_diff,
+                    tolerance: sync_tolerance,
+                });
+            }
+
+            // Check the node is on the same chain history as the reference
+            if let Some(checkpoint) = checkpoint.filter(|c| c.block <= block_number) {
+                let hash = session
+                    .call(&RpcRequest::eth_get_block_by_number(&format!("0x{:x}", checkpoint.block), false))
+                    .await?
+                    .into_block_hash()?;
 
-        // Check genesis block hash
-        let genesis_resp = self
-            .rpc_call(url, &RpcRequest::eth_get_block_by_number("0x0", false))
-            .await?;
-        let genesis_hash = genesis_resp
-            .result
-            .and_then(|v| v.get("hash").and_then(|h| h.as_str()).map(String::from))
-            .ok_or("No genesis hash in response")?;
-
-        if genesis_hash.to_lowercase() != expected_genesis_hash.to_lowercase() {
-            return Err("Genesis hash mismatch - possible honeypot".to_string());
+                if hash != checkpoint.hash {
+                    return Err(Error::ForkMismatch { block: checkpoint.block });
+                }
+            }
+
+            let latency_ms = start.elapsed().as_millis() as u64;
+
+            // Fingerprint client software (best effort, not part of validation)
+            let client = session
+                .call(&RpcRequest::web3_client_version())
+                .await
+                .ok()
+                .and_then(|r| r.result)
+                .and_then(|v| v.as_str().map(ClientVersion::parse));
+
+            Ok(ValidatedNode {
+                url: url.to_string(),
+                latency_ms,
+                block_number,
+                is_archive: false,
+                oldest_state_block: None,
+                client,
+                capabilities: Vec::new(),
+                security: None,
+                supports_subscriptions: None,
+                header_delay_ms: None,
+                tls: None,
+                host: None,
+            })
         }
+        .await;
+
+        session.close().await;
+        result
+    }
+
+    /// Subscribe to `newHeads` and wait for one header, allowing two block
+    /// times plus the request timeout. The header must be no more than
+    /// `sync_tolerance` behind the reference and not implausibly far ahead of
+    /// the node's own head. Records the outcome and the header's delay.
+    pub async fn check_subscription(
+        &self,
+        mut node: ValidatedNode,
+        block_time_ms: u64,
+        reference_block: u64,
+        sync_tolerance: u64,
+    ) -> ValidatedNode {
+        let wait = Duration::from_millis(block_time_ms.saturating_mul(2)) + HTTP_TIMEOUT;
+        let wait_blocks = wait.as_millis() as u64 / block_time_ms.max(1) + 1;
+
+        let result: Result<(u64, u64), Error> = match self.open_session(&node.url).await {
+            Ok((_permit, mut session)) => {
+                let result = async {
+                    let session = &mut session;
+                    let subscription = session.call(&RpcRequest::eth_subscribe("newHeads")).await?.into_result()?;
+                    let subscription = subscription
+                        .as_str()
+                        .ok_or_else(|| Error::InvalidResponse("subscription id is not a string".to_string()))?
+                        .to_string();
 
-        // Check sync status
-        let block_resp = self.rpc_call(url, &RpcRequest::eth_block_number()).await?;
-        let block_hex = block_resp
-            .result
-            .and_then(|v| v.as_str().map(String::from))
-            .ok_or("No block number in response")?;
-        let block_number = parse_hex_u64(&block_hex).ok_or("Invalid block number format")?;
-
-        let block_diff = if reference_block > block_number {
-            reference_block - block_number
-        } else {
-            block_number - reference_block
+                    // One deadline for the whole wait, so notifications for
+                    // other subscriptions cannot keep extending it
+                    let deadline = Instant::now() + wait;
+                    let header = loop {
+                        let remaining = deadline.saturating_duration_since(Instant::now());
+                        let notification = session.next_notification(remaining).await?;
+                        let params = &notification["params"];
+                        if params["subscription"].as_str() == Some(subscription.as_str()) {
+                            break params["result"].clone();
+                        }
+                    };
+                    let received_ms = SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map(|d| d.as_millis() as u64)
+                        .unwrap_or(0);
+
+                    let _ = session.call(&RpcRequest::eth_unsubscribe(&subscription)).await;
+
+                    let number = header["number"]
+                        .as_str()
+                        .and_then(parse_hex_u64)
+                        .ok_or_else(|| Error::InvalidResponse("header without number".to_string()))?;
+                    let timestamp = header["timestamp"]
+                        .as_str()
+                        .and_then(parse_hex_u64)
+                        .ok_or_else(|| Error::InvalidResponse("header without timestamp".to_string()))?;
+                    Ok((number, received_ms.saturating_sub(timestamp.saturating_mul(1000))))
+                }
+                .await;
+                session.close().await;
+                result
+            }
+            Err(e) => Err(e),
         };
 
-        if block_diff > sync_tolerance {
-            return Err(format!(
-                "Node not synced: {} blocks behind (tolerance: {})",
-                block_diff, sync_tolerance
-            ));
+        match result {
+            Ok((number, delay_ms))
+                if number.saturating_add(sync_tolerance) >= reference_block
+                    && number <= node.block_number.saturating_add(sync_tolerance).saturating_add(wait_blocks) =>
+            {
+                node.supports_subscriptions = Some(true);
+                node.header_delay_ms = Some(delay_ms);
+            }
+            Ok((number, _)) => {
+                log::debug!("{} streamed inconsistent header #{} (reference {})", node.url, number, reference_block);
+                node.supports_subscriptions = Some(false);
+            }
+            Err(e) => {
+                log::debug!("{} newHeads subscription failed: {}", node.url, e);
+                node.supports_subscriptions = Some(false);
+            }
+        }
+        node
+    }
+
+    /// Pipeline `requests` over one session, all failing if the node is unreachable
+    async fn call_all(&self, url: &str, requests: &[RpcRequest]) -> Vec<Result<RpcResponse, Error>> {
+        match self.open_session(url).await {
+            Ok((_permit, mut session)) => {
+                let responses = session.call_many(requests).await;
+                session.close().await;
+                responses
+            }
+            Err(e) => vec![Err(e); requests.len()],
         }
+    }
+
+    /// Probe which of `methods` the node exposes and record them on the node
+    pub async fn probe_capabilities(&self, mut node: ValidatedNode, methods: &[String]) -> ValidatedNode {
+        let requests: Vec<RpcRequest> = methods.iter().map(|m| capabilities::probe_request(m)).collect();
+        let responses = self.call_all(&node.url, &requests).await;
 
-        let latency_ms = start.elapsed().as_millis() as u64;
+        node.capabilities = methods
+            .iter()
+            .zip(responses)
+            .filter_map(|(method, response)| {
+                let response = response.ok()?;
+                capabilities::is_enabled(method, &response).then(|| method.clone())
+            })
+            .collect();
+        node
+    }
 
-        Ok(ValidatedNode {
-            url: url.to_string(),
-            latency_ms,
-            block_number,
-            is_archive: false,
-        })
+    /// Check the node for unlocked accounts and admin/personal/miner namespaces
+    pub async fn audit_security(&self, mut node: ValidatedNode) -> ValidatedNode {
+        let requests: Vec<RpcRequest> = AUDIT_METHODS.iter().map(|m| SecurityAudit::request(m)).collect();
+        let responses = self.call_all(&node.url, &requests).await;
+
+        let mut audit = SecurityAudit::default();
+        for (method, response) in AUDIT_METHODS.iter().zip(responses) {
+            if let Ok(response) = response {
+                audit.record(method, &response);
+            }
+        }
+        node.security = Some(audit);
+        node
     }
 }
 
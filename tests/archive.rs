@@ -0,0 +1,64 @@
+mod common;
+
+use common::MockServer;
+use node_finder::validator::archive::ArchiveValidator;
+use node_finder::Error;
+use serde_json::{json, Value};
+
+/// A node that serves state from `oldest` up to its head
+async fn pruned_node(oldest: u64) -> MockServer {
+    MockServer::start(move |request| {
+        let body: Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(body["method"], "eth_getBalance");
+        let block = u64::from_str_radix(body["params"][1].as_str().unwrap().trim_start_matches("0x"), 16).unwrap();
+        let response = if block >= oldest {
+            json!({"jsonrpc": "2.0", "id": body["id"], "result": "0x0"})
+        } else {
+            json!({"jsonrpc": "2.0", "id": body["id"], "error": {"code": -32000, "message": "missing trie node"}})
+        };
+        (200, response.to_string())
+    })
+    .await
+}
+
+#[tokio::test]
+async fn finds_the_oldest_state_block() {
+    let server = pruned_node(1_234).await;
+    let archive = ArchiveValidator::new();
+
+    let oldest = archive.find_oldest_state_block(&server.url, 10_000, &[5_000], 0).await.unwrap();
+    assert_eq!(oldest, 1_234);
+    // Head, block 1, the 5000 probe, then about log2(5000) halvings
+    assert!(server.requests().len() <= 3 + 14, "{} requests", server.requests().len());
+}
+
+#[tokio::test]
+async fn full_archive_reports_genesis() {
+    let server = pruned_node(0).await;
+    let oldest = ArchiveValidator::new().find_oldest_state_block(&server.url, 10_000, &[], 0).await.unwrap();
+    assert_eq!(oldest, 0);
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn head_without_state_is_not_archive() {
+    let server = pruned_node(u64::MAX).await;
+    let result = ArchiveValidator::new().find_oldest_state_block(&server.url, 10_000, &[], 0).await;
+    assert!(matches!(result, Err(Error::ArchiveMissing { block: 10_000 })));
+}
+
+#[tokio::test]
+async fn searches_no_lower_than_the_first_state_block() {
+    // A post-migration node: nothing older than the migration block exists
+    let server = pruned_node(22_207_817).await;
+    let archive = ArchiveValidator::new();
+
+    let oldest = archive.find_oldest_state_block(&server.url, 30_000_000, &[1, 100], 22_207_817).await.unwrap();
+    assert_eq!(oldest, 22_207_817);
+    // Head and the migration block only; the probes below it are skipped
+    assert_eq!(server.requests().len(), 2);
+
+    let pruned = pruned_node(25_000_000).await;
+    let oldest = archive.find_oldest_state_block(&pruned.url, 30_000_000, &[], 22_207_817).await.unwrap();
+    assert_eq!(oldest, 25_000_000);
+}
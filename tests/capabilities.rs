@@ -0,0 +1,63 @@
+use node_finder::validator::capabilities::{is_enabled, probe_request, supports_tracing};
+use node_finder::validator::RpcResponse;
+use serde_json::json;
+
+fn error(code: i64, message: &str) -> RpcResponse {
+    serde_json::from_value(json!({"jsonrpc": "2.0", "id": 1, "error": {"code": code, "message": message}})).unwrap()
+}
+
+#[test]
+fn argument_errors_count_as_enabled() {
+    let result: RpcResponse = serde_json::from_value(json!({"jsonrpc": "2.0", "id": 1, "result": []})).unwrap();
+    assert!(is_enabled("trace_block", &result));
+    assert!(is_enabled("debug_traceTransaction", &error(-32000, "transaction 0x00 not found")));
+    assert!(is_enabled("debug_traceBlockByNumber", &error(-32602, "invalid argument 1")));
+}
+
+#[test]
+fn disabled_namespaces_are_not_enabled() {
+    assert!(!is_enabled("txpool_content", &error(-32601, "Method not found")));
+    assert!(!is_enabled(
+        "debug_traceTransaction",
+        &error(-32000, "the method debug_traceTransaction does not exist/is not available")
+    ));
+    assert!(!is_enabled("trace_block", &error(-32000, "trace namespace is disabled")));
+}
+
+#[test]
+fn probes_point_at_genesis_or_a_zero_hash() {
+    let trace = probe_request("debug_traceTransaction");
+    assert_eq!(trace.params, vec![json!(format!("0x{}", "0".repeat(64)))]);
+    assert_eq!(probe_request("trace_block").params, vec![json!("0x0")]);
+    assert!(probe_request("txpool_status").params.is_empty());
+
+    assert!(supports_tracing(&["txpool_status".to_string(), "trace_block".to_string()]));
+    assert!(!supports_tracing(&["txpool_content".to_string()]));
+}
+
+#[test]
+fn proxy_and_gateway_rejections_are_not_enabled() {
+    for message in [
+        "method not allowed",
+        "debug_traceTransaction is not whitelisted",
+        "unauthorized",
+        "rate limit exceeded",
+        "Too many requests, please slow down",
+        "502 Bad Gateway",
+        "internal error",
+    ] {
+        assert!(!is_enabled("debug_traceTransaction", &error(-32000, message)), "{}", message);
+    }
+    assert!(!is_enabled("trace_block", &error(-32005, "daily request count exceeded, request rate limited")));
+    assert!(!is_enabled("txpool_status", &error(-32602, "invalid params: method not whitelisted")));
+}
+
+#[test]
+fn tracer_errors_count_as_enabled() {
+    assert!(is_enabled("debug_traceBlockByNumber", &error(-32000, "genesis is not traceable")));
+    assert!(is_enabled("trace_block", &error(-32000, "Cannot find block 0x0")));
+    assert!(is_enabled("txpool_content", &error(-32602, "missing value for required argument 0")));
+
+    let empty: RpcResponse = serde_json::from_value(json!({"jsonrpc": "2.0", "id": 1})).unwrap();
+    assert!(!is_enabled("txpool_status", &empty));
+}
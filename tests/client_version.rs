@@ -0,0 +1,39 @@
+use node_finder::validator::client::{ClientFamily, ClientVersion};
+
+#[test]
+fn parses_standard_client_versions() {
+    let geth = ClientVersion::parse("Geth/v1.13.5-stable-916d6a44/linux-amd64/go1.21.4");
+    assert_eq!(geth.family, ClientFamily::Geth);
+    assert_eq!(geth.version.as_deref(), Some("v1.13.5-stable-916d6a44"));
+    assert_eq!(geth.os.as_deref(), Some("linux-amd64"));
+    assert_eq!(geth.runtime.as_deref(), Some("go1.21.4"));
+    assert_eq!(geth.label(), "Geth v1.13.5");
+
+    let nethermind = ClientVersion::parse("Nethermind/v1.25.4+20b10b35/linux-x64/dotnet8.0.2");
+    assert_eq!(nethermind.family, ClientFamily::Nethermind);
+    assert_eq!(nethermind.runtime.as_deref(), Some("dotnet8.0.2"));
+    assert_eq!(nethermind.label(), "Nethermind v1.25.4");
+
+    assert_eq!(ClientVersion::parse("reth/v0.2.0-beta.6-ac29b4b73/x86_64-unknown-linux-gnu").family, ClientFamily::Reth);
+    assert_eq!(ClientVersion::parse("bsc-geth/v1.4.5/linux-amd64/go1.21.8").family, ClientFamily::Geth);
+}
+
+#[test]
+fn finds_the_version_after_an_identity_segment() {
+    let parsed = ClientVersion::parse("Geth/my-node/v1.13.5-stable/linux-amd64/go1.21.4");
+    assert_eq!(parsed.name, "Geth");
+    assert_eq!(parsed.version.as_deref(), Some("v1.13.5-stable"));
+    assert_eq!(parsed.os.as_deref(), Some("linux-amd64"));
+}
+
+#[test]
+fn keeps_unknown_clients_by_name() {
+    let parsed = ClientVersion::parse(" CoolClient ");
+    assert_eq!(parsed.family, ClientFamily::Other);
+    assert_eq!(parsed.version, None);
+    assert_eq!(parsed.label(), "CoolClient");
+    assert_eq!(parsed.raw, "CoolClient");
+
+    assert_eq!(ClientFamily::from_code("erigon"), Some(ClientFamily::Erigon));
+    assert_eq!(ClientFamily::from_code("other"), None);
+}
@@ -0,0 +1,190 @@
+//! Minimal HTTP server standing in for third-party APIs in tests
+
+// Each test crate uses a different part of it
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// A request as the mock server received it
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub query: HashMap<String, String>,
+    /// Header names lowercased
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+type Handler = dyn Fn(&RecordedRequest) -> (u16, String) + Send + Sync;
+
+pub struct MockServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl MockServer {
+    /// Serve every request with `handler`'s status and JSON body
+    pub async fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&RecordedRequest) -> (u16, String) + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut stream, _)) = listener.accept().await else {
+                    return;
+                };
+                let handler = handler.clone();
+                let recorded = recorded.clone();
+                tokio::spawn(async move {
+                    let Some(request) = read_request(&mut stream).await else {
+                        return;
+                    };
+                    let (status, body) = handler(&request);
+                    recorded.lock().unwrap().push(request);
+
+                    let response = format!(
+                        "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        body.len(),
+                        body
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                    let _ = stream.shutdown().await;
+                });
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn read_request(stream: &mut tokio::net::TcpStream) -> Option<RecordedRequest> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+        if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let target = request_line.next()?;
+
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+        .collect();
+
+    // Read the whole body so the client sees a clean response
+    let content_length: usize = headers.get("content-length").and_then(|v| v.parse().ok()).unwrap_or(0);
+    let mut body = buffer[header_end + 4..].to_vec();
+    while body.len() < content_length {
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = url::form_urlencoded::parse(query.as_bytes()).into_owned().collect();
+
+    Some(RecordedRequest {
+        method,
+        path: path.to_string(),
+        query,
+        headers,
+        body: String::from_utf8_lossy(&body).to_string(),
+    })
+}
+
+/// Hash the mock JSON-RPC nodes report for block `number` (above 0)
+pub fn block_hash(number: u64) -> String {
+    format!("0x{:064x}", number)
+}
+
+/// A JSON-RPC node on chain `chain_id` at height `head`, with `genesis` as
+/// block 0 and `block_hash(n)` for every other block
+pub async fn rpc_node(chain_id: u64, genesis: &str, head: u64) -> MockServer {
+    let genesis = genesis.to_string();
+    MockServer::start(move |request| {
+        let body: serde_json::Value = serde_json::from_str(&request.body).unwrap_or_default();
+        let result = match body["method"].as_str().unwrap_or_default() {
+            "eth_chainId" => serde_json::json!(format!("0x{:x}", chain_id)),
+            "eth_blockNumber" => serde_json::json!(format!("0x{:x}", head)),
+            "eth_getBlockByNumber" => {
+                let tag = body["params"][0].as_str().unwrap_or("0x0");
+                let number = u64::from_str_radix(tag.trim_start_matches("0x"), 16).unwrap_or(0);
+                let hash = if number == 0 { genesis.clone() } else { block_hash(number) };
+                serde_json::json!({"number": tag, "hash": hash})
+            }
+            "web3_clientVersion" => serde_json::json!("Geth/v1.13.5-stable/linux-amd64/go1.21.4"),
+            method => {
+                let error = serde_json::json!({"code": -32601, "message": format!("the method {} does not exist/is not available", method)});
+                return (200, serde_json::json!({"jsonrpc": "2.0", "id": body["id"], "error": error}).to_string());
+            }
+        };
+        (200, serde_json::json!({"jsonrpc": "2.0", "id": body["id"], "result": result}).to_string())
+    })
+    .await
+}
+
+/// WebSocket server answering every JSON-RPC request with the messages
+/// `handler` returns for it, in order. Returns the `ws://` URL.
+pub async fn ws_server<F>(handler: F) -> String
+where
+    F: Fn(&serde_json::Value) -> Vec<serde_json::Value> + Send + Sync + 'static,
+{
+    use futures::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::Message;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let handler = Arc::new(handler);
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let handler = handler.clone();
+            tokio::spawn(async move {
+                let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await else {
+                    return;
+                };
+                while let Some(Ok(message)) = ws.next().await {
+                    let Message::Text(text) = message else {
+                        continue;
+                    };
+                    let Ok(request) = serde_json::from_str::<serde_json::Value>(&text) else {
+                        continue;
+                    };
+                    for reply in handler(&request) {
+                        if ws.send(Message::Text(reply.to_string())).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            });
+        }
+    });
+    url
+}
@@ -0,0 +1,461 @@
+mod common;
+
+use base64::Engine;
+use common::MockServer;
+use node_finder::discovery::censys::CensysClient;
+use node_finder::discovery::fofa::FofaClient;
+use node_finder::discovery::zoomeye::ZoomEyeClient;
+use node_finder::discovery::{Candidate, CertificateInfo, Discovery, DiscoveryQuery, DiscoverySource};
+use node_finder::shodan::client::DEFAULT_FACETS;
+use node_finder::shodan::{ShodanClient, ShodanQuery};
+use node_finder::Error;
+use serde_json::json;
+
+fn query() -> DiscoveryQuery {
+    DiscoveryQuery {
+        chain_id: 1,
+        country_code: Some("DE".to_string()),
+        min_results: 30,
+        max_pages: 3,
+        shodan: Default::default(),
+    }
+}
+
+fn shodan_body() -> String {
+    json!({
+        "total": 2,
+        "matches": [
+            {
+                "ip_str": "203.0.113.1",
+                "port": 8545,
+                "location": {"country_code": "DE"},
+                "hostnames": []
+            },
+            {
+                "ip_str": "203.0.113.2",
+                "port": 443,
+                "location": {"country_code": "DE"},
+                "hostnames": ["node.example.org"],
+                "ssl": {"cert": {
+                    "expires": "20300101000000Z",
+                    "issuer": {"O": "Let's Encrypt", "CN": "R3"},
+                    "subject": {"CN": "rpc.example.org"}
+                }}
+            }
+        ]
+    })
+    .to_string()
+}
+
+#[tokio::test]
+async fn shodan_parses_matches_and_sends_key() {
+    let server = MockServer::start(|_| (200, shodan_body())).await;
+    let shodan = ShodanClient::new("shodan-key".to_string()).with_base_url(&server.url);
+
+    let result = shodan.search(&query()).await.unwrap();
+
+    assert_eq!(result.total, 2);
+    assert_eq!(result.pages_fetched, 1);
+    assert_eq!(result.candidates.len(), 2);
+
+    let plain = &result.candidates[0];
+    assert_eq!(plain.http_url(), "http://203.0.113.1:8545");
+    assert!(!plain.is_tls());
+
+    let tls = &result.candidates[1];
+    assert_eq!(tls.hostnames, ["rpc.example.org", "node.example.org"]);
+    assert_eq!(tls.https_urls()[0], "https://rpc.example.org");
+    let certificate = tls.certificate.as_ref().unwrap();
+    assert_eq!(certificate.issuer.as_deref(), Some("Let's Encrypt"));
+    assert_eq!(certificate.expires_at, Some(1_893_456_000));
+    assert_eq!(tls.sources, ["shodan"]);
+
+    let requests = server.requests();
+    assert_eq!(requests[0].method, "GET");
+    assert_eq!(requests[0].path, "/shodan/host/search");
+    assert_eq!(requests[0].query["key"], "shodan-key");
+    assert!(requests[0].query["query"].contains("\"Chain Id: 0x1\""));
+    assert!(requests[0].query["query"].contains("country:DE"));
+}
+
+#[tokio::test]
+async fn shodan_stops_paging_when_credits_run_out() {
+    let server = MockServer::start(|request| {
+        if request.path == "/api-info" {
+            return (200, json!({"query_credits": 1}).to_string());
+        }
+        let page: usize = request.query["page"].parse().unwrap();
+        let matches: Vec<_> = (0..100)
+            .map(|i| json!({"ip_str": format!("10.{}.0.{}", page, i), "port": 8545, "location": {}}))
+            .collect();
+        (200, json!({"total": 1000, "matches": matches}).to_string())
+    })
+    .await;
+    let shodan = ShodanClient::new("shodan-key".to_string()).with_base_url(&server.url);
+
+    let mut query = query();
+    query.min_results = 1000;
+    query.max_pages = 5;
+    let result = shodan.search(&query).await.unwrap();
+
+    // Page 1 was paid for, the one remaining credit buys page 2
+    assert_eq!(result.pages_fetched, 2);
+    assert_eq!(result.candidates.len(), 200);
+    let paths: Vec<String> = server.requests().into_iter().map(|r| r.path).collect();
+    assert_eq!(paths, ["/shodan/host/search", "/api-info", "/shodan/host/search"]);
+}
+
+#[tokio::test]
+async fn shodan_sends_filters_and_keeps_extra_ports() {
+    let server = MockServer::start(|_| {
+        let body = json!({
+            "total": 2,
+            "matches": [
+                {"ip_str": "203.0.113.5", "port": 8547, "location": {"country_code": "DE"}},
+                {"ip_str": "203.0.113.6", "port": 8545, "location": {"country_code": "DE"}}
+            ]
+        });
+        (200, body.to_string())
+    })
+    .await;
+    let shodan = ShodanClient::new("shodan-key".to_string()).with_base_url(&server.url);
+
+    let mut query = query();
+    query.shodan = ShodanQuery::parse("port:8547 product:Nethermind asn:AS24940").unwrap();
+    let result = shodan.search(&query).await.unwrap();
+
+    // Only the extra port is validated outside the default RPC ports
+    let listed: Vec<bool> = result.candidates.iter().map(|c| c.listed).collect();
+    assert_eq!(listed, [true, false]);
+
+    let sent = &server.requests()[0].query["query"];
+    assert!(sent.starts_with("port:8545,8546,443,8547 "));
+    assert!(sent.contains("\"Chain Id: 1\" OR product:\"Nethermind\""));
+    assert!(sent.contains("asn:AS24940"));
+}
+
+#[tokio::test]
+async fn shodan_keeps_host_metadata() {
+    let server = MockServer::start(|_| {
+        let body = json!({
+            "total": 1,
+            "matches": [{
+                "ip_str": "203.0.113.7",
+                "port": 8545,
+                "location": {"country_code": "DE", "city": "Falkenstein"},
+                "hostnames": ["static.7.113.0.203.clients.example.net"],
+                "org": "Hetzner Online GmbH",
+                "isp": "Hetzner Online GmbH",
+                "asn": "AS24940",
+                "timestamp": "2024-01-15T10:23:45.123456",
+                "tags": ["cloud"],
+                "ethereum_rpc": {"chain_id": "0x1", "client": "Geth", "version": "v1.13.5-stable", "block_number": "0x12a05f2"}
+            }]
+        });
+        (200, body.to_string())
+    })
+    .await;
+    let shodan = ShodanClient::new("shodan-key".to_string()).with_base_url(&server.url);
+
+    let result = shodan.search(&query()).await.unwrap();
+    let candidate = &result.candidates[0];
+    let host = candidate.host.as_ref().unwrap();
+
+    assert_eq!(host.org.as_deref(), Some("Hetzner Online GmbH"));
+    assert_eq!(host.asn.as_deref(), Some("AS24940"));
+    assert_eq!(host.city.as_deref(), Some("Falkenstein"));
+    assert_eq!(host.last_update, Some(1_705_314_225));
+    let rpc = host.rpc.as_ref().unwrap();
+    assert_eq!(rpc.client.as_deref(), Some("Geth"));
+    assert_eq!(rpc.version.as_deref(), Some("v1.13.5-stable"));
+    assert_eq!(rpc.block_number, Some(19_531_250));
+    assert!(host.is_hosting());
+
+    // Validated nodes get the hostnames along with the metadata
+    let info = candidate.host_info().unwrap();
+    assert_eq!(info.hostnames, ["static.7.113.0.203.clients.example.net"]);
+    assert_eq!(info.label().as_deref(), Some("Hetzner Online GmbH, Falkenstein"));
+}
+
+#[tokio::test]
+async fn shodan_counts_facets_without_search() {
+    let server = MockServer::start(|_| {
+        let body = json!({
+            "total": 412,
+            "matches": [],
+            "facets": {
+                "country": [{"count": 120, "value": "US"}, {"count": 80, "value": "DE"}],
+                "org": [{"count": 60, "value": "Amazon.com, Inc."}],
+                "asn": [{"count": 60, "value": "AS16509"}]
+            }
+        });
+        (200, body.to_string())
+    })
+    .await;
+    let shodan = ShodanClient::new("shodan-key".to_string()).with_base_url(&server.url);
+
+    let mut query = query();
+    query.country_code = None;
+    let facets = shodan.facets(&query, &DEFAULT_FACETS).await.unwrap();
+
+    assert_eq!(facets.total, 412);
+    assert_eq!(facets.values("country"), [("US".to_string(), 120), ("DE".to_string(), 80)]);
+    assert_eq!(facets.values("org"), [("Amazon.com, Inc.".to_string(), 60)]);
+    let names: Vec<&str> = facets.facets.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, DEFAULT_FACETS);
+
+    let requests = server.requests();
+    assert_eq!(requests[0].path, "/shodan/host/count");
+    assert_eq!(requests[0].query["facets"], "country:10,org:10,asn:10");
+    assert!(!requests[0].query["query"].contains("country:"));
+}
+
+#[tokio::test]
+async fn censys_follows_cursor_with_basic_auth() {
+    let server = MockServer::start(|request| {
+        let hit = |i: usize| {
+            json!({
+                "ip": format!("198.51.100.{}", i),
+                "services": [
+                    {"port": 22, "extended_service_name": "SSH"},
+                    {"port": 8545, "extended_service_name": "HTTP"}
+                ],
+                "location": {"country_code": "DE"}
+            })
+        };
+        let body = match request.query.get("cursor").map(String::as_str) {
+            None => json!({"code": 200, "result": {
+                "total": 101,
+                "hits": (0..100).map(hit).collect::<Vec<_>>(),
+                "links": {"next": "page2", "prev": ""}
+            }}),
+            Some("page2") => json!({"code": 200, "result": {
+                "total": 101,
+                "hits": [{
+                    "ip": "198.51.100.200",
+                    "matched_services": [{"port": 443, "extended_service_name": "HTTPS"}],
+                    "dns": {"reverse_dns": {"names": ["rpc.example.net"]}}
+                }],
+                "links": {"next": "", "prev": "page1"}
+            }}),
+            Some(_) => return (400, "{}".to_string()),
+        };
+        (200, body.to_string())
+    })
+    .await;
+    let censys = CensysClient::new("censys-id".to_string(), "censys-secret".to_string()).with_base_url(&server.url);
+
+    let mut query = query();
+    query.min_results = 150;
+    let result = censys.search(&query).await.unwrap();
+
+    assert_eq!(result.pages_fetched, 2);
+    assert_eq!(result.total, 101);
+    // SSH services are not RPC candidates
+    assert_eq!(result.candidates.len(), 101);
+    assert!(result.candidates[..100].iter().all(|c| c.port == 8545 && !c.is_tls()));
+    let last = &result.candidates[100];
+    assert_eq!((last.port, last.is_tls()), (443, true));
+    assert_eq!(last.hostnames, ["rpc.example.net"]);
+
+    let requests = server.requests();
+    let expected_auth = format!(
+        "Basic {}",
+        base64::engine::general_purpose::STANDARD.encode("censys-id:censys-secret")
+    );
+    assert!(requests.iter().all(|r| r.path == "/v2/hosts/search"));
+    assert!(requests.iter().all(|r| r.headers["authorization"] == expected_auth));
+    assert!(requests[0].query["q"].contains("location.country_code: DE"));
+}
+
+#[tokio::test]
+async fn fofa_sends_base64_query_and_reports_api_errors() {
+    let server = MockServer::start(|request| {
+        if request.query.get("key").map(String::as_str) != Some("fofa-key") {
+            return (200, json!({"error": true, "errmsg": "[-700] Account Invalid"}).to_string());
+        }
+        let body = json!({
+            "error": false,
+            "size": 2,
+            "page": 1,
+            "results": [
+                ["192.0.2.10", "8545", "DE", "http", ""],
+                ["192.0.2.11", "443", "DE", "https", "rpc.example.com"],
+                ["malformed row"]
+            ]
+        });
+        (200, body.to_string())
+    })
+    .await;
+
+    let fofa = FofaClient::new(None, "fofa-key".to_string()).with_base_url(&server.url);
+    let result = fofa.search(&query()).await.unwrap();
+
+    assert_eq!(result.total, 2);
+    assert_eq!(result.candidates.len(), 2);
+    assert_eq!(result.candidates[0].country_code.as_deref(), Some("DE"));
+    assert!(result.candidates[1].is_tls());
+    assert_eq!(result.candidates[1].https_urls()[0], "https://rpc.example.com");
+
+    let request = &server.requests()[0];
+    assert_eq!(request.path, "/api/v1/search/all");
+    let decoded = base64::engine::general_purpose::STANDARD
+        .decode(&request.query["qbase64"])
+        .unwrap();
+    let decoded = String::from_utf8(decoded).unwrap();
+    assert!(decoded.contains("port=\"8545\""));
+    assert!(decoded.contains("country=\"DE\""));
+
+    let bad_key = FofaClient::new(None, "wrong".to_string()).with_base_url(&server.url);
+    let error = bad_key.search(&query()).await.unwrap_err();
+    assert!(matches!(error, Error::Discovery { provider: "fofa", ref message } if message.contains("Account Invalid")));
+}
+
+#[tokio::test]
+async fn zoomeye_authenticates_with_api_key_header() {
+    let server = MockServer::start(|request| {
+        if request.headers.get("api-key").map(String::as_str) != Some("zoomeye-key") {
+            return (401, json!({"error": "bad_request"}).to_string());
+        }
+        let body = json!({
+            "total": 2,
+            "available": 2,
+            "matches": [
+                {
+                    "ip": "192.0.2.20",
+                    "portinfo": {"port": 8546, "service": "http", "hostname": ""},
+                    "geoinfo": {"country": {"code": "DE"}}
+                },
+                {
+                    "ip": "192.0.2.21",
+                    "portinfo": {"port": 80, "service": "http", "hostname": ""},
+                    "geoinfo": {"country": {"code": "DE"}}
+                }
+            ]
+        });
+        (200, body.to_string())
+    })
+    .await;
+
+    let zoomeye = ZoomEyeClient::new("zoomeye-key".to_string()).with_base_url(&server.url);
+    let result = zoomeye.search(&query()).await.unwrap();
+
+    // Port 80 is not an RPC port
+    assert_eq!(result.candidates.len(), 1);
+    assert_eq!(result.candidates[0].ip, "192.0.2.20");
+    assert!(result.candidates[0].is_ws_port());
+    assert_eq!(server.requests()[0].path, "/host/search");
+
+    let unauthorized = ZoomEyeClient::new("wrong".to_string()).with_base_url(&server.url);
+    assert!(matches!(
+        unauthorized.search(&query()).await,
+        Err(Error::Discovery { provider: "zoomeye", .. })
+    ));
+}
+
+#[tokio::test]
+async fn discovery_merges_duplicates_across_sources() {
+    let shodan_server = MockServer::start(|_| (200, shodan_body())).await;
+    let fofa_server = MockServer::start(|_| {
+        let body = json!({
+            "error": false,
+            "size": 2,
+            "results": [
+                // Also found by Shodan, without TLS details
+                ["203.0.113.2", "443", "", "https", "mirror.example.org"],
+                ["192.0.2.30", "8545", "DE", "http", ""]
+            ]
+        });
+        (200, body.to_string())
+    })
+    .await;
+    let zoomeye_server = MockServer::start(|_| (500, "{}".to_string())).await;
+
+    let discovery = Discovery::new()
+        .with_source(ShodanClient::new("key".to_string()).with_base_url(&shodan_server.url))
+        .with_source(FofaClient::new(None, "key".to_string()).with_base_url(&fofa_server.url))
+        .with_source(ZoomEyeClient::new("key".to_string()).with_base_url(&zoomeye_server.url));
+
+    let merged = discovery.search(&query()).await.unwrap();
+
+    assert_eq!(merged.duplicates, 1);
+    let addresses: Vec<(&str, u16)> = merged.candidates.iter().map(|c| (c.ip.as_str(), c.port)).collect();
+    assert_eq!(
+        addresses,
+        [("203.0.113.1", 8545), ("203.0.113.2", 443), ("192.0.2.30", 8545)]
+    );
+
+    let shared = &merged.candidates[1];
+    assert_eq!(shared.sources, ["shodan", "fofa"]);
+    assert_eq!(shared.hostnames, ["rpc.example.org", "node.example.org", "mirror.example.org"]);
+    // Shodan's certificate details are kept over FOFA's bare TLS flag
+    assert!(shared.certificate.as_ref().unwrap().expires_at.is_some());
+
+    assert_eq!(merged.sources["shodan"].candidates, 2);
+    assert_eq!(merged.sources["fofa"].candidates, 2);
+    assert!(merged.sources["zoomeye"].error.is_some());
+}
+
+#[tokio::test]
+async fn discovery_fails_only_when_every_source_fails() {
+    let down = MockServer::start(|_| (503, "{}".to_string())).await;
+    let discovery = Discovery::new()
+        .with_source(ShodanClient::new("key".to_string()).with_base_url(&down.url))
+        .with_source(ZoomEyeClient::new("key".to_string()).with_base_url(&down.url));
+
+    let error = discovery.search(&query()).await.unwrap_err();
+    assert!(matches!(error, Error::Discovery { provider: "shodan", .. }));
+
+    assert!(Discovery::new().search(&query()).await.is_err());
+}
+
+/// Base URL of a port nothing listens on
+async fn closed_port() -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+    url
+}
+
+#[tokio::test]
+async fn errors_do_not_leak_credentials() {
+    let down = closed_port().await;
+    let garbled = MockServer::start(|_| (200, "<html>maintenance</html>".to_string())).await;
+
+    for base_url in [down.as_str(), garbled.url.as_str()] {
+        let sources: Vec<Box<dyn DiscoverySource>> = vec![
+            Box::new(ShodanClient::new("secret-shodan-key".to_string()).with_base_url(base_url)),
+            Box::new(
+                FofaClient::new(Some("owner@example.org".to_string()), "secret-fofa-key".to_string())
+                    .with_base_url(base_url),
+            ),
+            Box::new(ZoomEyeClient::new("secret-zoomeye-key".to_string()).with_base_url(base_url)),
+            Box::new(CensysClient::new("secret-id".to_string(), "secret-censys".to_string()).with_base_url(base_url)),
+        ];
+        for source in sources {
+            let error = source.search(&query()).await.unwrap_err().to_string();
+            assert!(error.contains("failed"), "{}", error);
+            assert!(!error.contains("secret") && !error.contains("owner@"), "{} leaks credentials: {}", source.name(), error);
+        }
+    }
+}
+
+#[test]
+fn candidate_urls_bracket_ipv6() {
+    let mut candidate = Candidate::new("shodan", "2001:db8::1".to_string(), 8545);
+    assert_eq!(candidate.http_url(), "http://[2001:db8::1]:8545");
+    assert_eq!(
+        candidate.ws_urls(&[8546], &["/ws".to_string()]),
+        ["ws://[2001:db8::1]:8545/ws", "ws://[2001:db8::1]:8546/ws", "wss://[2001:db8::1]/ws"]
+    );
+
+    candidate.certificate = Some(CertificateInfo::default());
+    assert_eq!(candidate.https_urls(), ["https://[2001:db8::1]:8545"]);
+
+    // Imports and scans hand in bracketed addresses already; both forms
+    // are stored the same way so the host merges across sources
+    let imported = Candidate::new("import", "[2001:DB8:0::1]".to_string(), 443);
+    assert_eq!(imported.http_url(), "http://[2001:db8::1]:443");
+    assert_eq!(imported.ip, Candidate::new("shodan", "2001:db8::1".to_string(), 443).ip);
+    assert_eq!(Candidate::new("fofa", "10.0.0.1".to_string(), 8545).http_url(), "http://10.0.0.1:8545");
+}
@@ -0,0 +1,28 @@
+use node_finder::Error;
+
+#[tokio::test]
+async fn classifies_reqwest_failures() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    let err: Error = reqwest::get(&url).await.unwrap_err().into();
+    assert_eq!(err.reason(), "connect");
+}
+
+#[test]
+fn reasons_are_stable_per_variant() {
+    assert_eq!(Error::Timeout.reason(), "timeout");
+    assert_eq!(Error::NotSynced { behind: 40, tolerance: 10 }.reason(), "not_synced");
+    assert_eq!(Error::ChainIdMismatch { expected: 1, actual: 56 }.reason(), "chain_id_mismatch");
+    assert_eq!(Error::ReferenceUnavailable(Box::new(Error::Timeout)).reason(), "reference_unavailable");
+}
+
+#[test]
+fn messages_carry_the_details() {
+    assert_eq!(
+        Error::NotSynced { behind: 40, tolerance: 10 }.to_string(),
+        "Node not synced: 40 blocks behind (tolerance: 10)"
+    );
+    assert_eq!(Error::RpcError { code: -32601, message: "method not found".into() }.to_string(), "RPC error -32601: method not found");
+}
@@ -0,0 +1,59 @@
+use node_finder::config::UserConfig;
+use node_finder::discovery::hosting::{HostInfo, NetworkFilter};
+
+fn host(org: &str, asn: &str) -> HostInfo {
+    HostInfo {
+        org: Some(org.to_string()),
+        isp: Some(org.to_string()),
+        asn: Some(asn.to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn matches_providers_by_short_name_asn_and_org_text() {
+    let aws = host("Amazon.com, Inc.", "AS16509");
+    assert!(aws.matches_provider("aws"));
+    assert!(aws.matches_provider("as16509"));
+    assert!(aws.matches_provider("Amazon"));
+    assert!(!aws.matches_provider("hetzner"));
+    assert!(!aws.matches_provider(" "));
+
+    let cloud = HostInfo {
+        cloud: Some("Google".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(cloud.provider(), Some("Google"));
+    assert!(cloud.is_hosting());
+
+    let home = host("Deutsche Telekom AG", "AS3320");
+    assert!(!home.is_hosting());
+    assert!(aws.is_hosting());
+}
+
+#[test]
+fn config_filters_networks_and_excluded_providers() {
+    let aws = host("Amazon.com, Inc.", "AS16509");
+    let hetzner = host("Hetzner Online GmbH", "AS24940");
+    let home = host("Deutsche Telekom AG", "AS3320");
+
+    let mut config = UserConfig::default();
+    assert!(config.accepts_host(None));
+    assert!(config.accepts_host(Some(&aws)));
+
+    config.excluded_providers = vec!["aws".to_string()];
+    assert!(!config.accepts_host(Some(&aws)));
+    assert!(config.accepts_host(Some(&hetzner)));
+    // Without metadata there is nothing to exclude on
+    assert!(config.accepts_host(None));
+
+    config.network_filter = NetworkFilter::Residential;
+    assert!(config.accepts_host(Some(&home)));
+    assert!(!config.accepts_host(Some(&hetzner)));
+    assert!(!config.accepts_host(None));
+
+    config.network_filter = NetworkFilter::Hosting;
+    assert!(config.accepts_host(Some(&hetzner)));
+    assert!(!config.accepts_host(Some(&aws)));
+    assert!(!config.accepts_host(Some(&home)));
+}
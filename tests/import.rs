@@ -0,0 +1,134 @@
+use node_finder::discovery::import::ImportedList;
+use node_finder::discovery::{Discovery, DiscoveryQuery};
+
+fn addresses(list: &ImportedList) -> Vec<(&str, u16)> {
+    list.candidates().iter().map(|c| (c.ip.as_str(), c.port)).collect()
+}
+
+#[test]
+fn parses_address_lists() {
+    let list = ImportedList::parse(
+        "# nodes from another team\n\
+         203.0.113.1:8545\n\
+         203.0.113.2\n\
+         \n\
+         saddr,sport\n\
+         203.0.113.3,8547\n\
+         [2001:db8::1]:8545\n\
+         203.0.113.1:8545 # duplicate\n\
+         not an address\n",
+    );
+
+    assert_eq!(
+        addresses(&list),
+        [
+            ("203.0.113.1", 8545),
+            ("203.0.113.2", 8545),
+            ("203.0.113.3", 8547),
+            ("[2001:db8::1]", 8545)
+        ]
+    );
+    // The CSV header and the free text
+    assert_eq!(list.skipped, 2);
+    assert!(list.candidates().iter().all(|c| c.listed && c.endpoint.is_none()));
+    assert_eq!(list.candidates()[3].http_url(), "http://[2001:db8::1]:8545");
+}
+
+#[test]
+fn parses_masscan_output() {
+    let json = r#"[
+{   "ip": "198.51.100.1",   "timestamp": "1700000000", "ports": [ {"port": 8545, "proto": "tcp", "status": "open", "reason": "syn-ack", "ttl": 54} ] },
+{   "ip": "198.51.100.2",   "timestamp": "1700000000", "ports": [ {"port": 8546, "proto": "tcp", "status": "open", "reason": "syn-ack", "ttl": 54} ] },
+]"#;
+    let list = ImportedList::parse(json);
+    assert_eq!(addresses(&list), [("198.51.100.1", 8545), ("198.51.100.2", 8546)]);
+
+    let list = ImportedList::parse(
+        "#masscan\n\
+         open tcp 8545 198.51.100.3 1700000000\n\
+         open tcp 9650 198.51.100.4 1700000000\n\
+         # end\n",
+    );
+    assert_eq!(addresses(&list), [("198.51.100.3", 8545), ("198.51.100.4", 9650)]);
+    assert_eq!(list.skipped, 0);
+}
+
+#[test]
+fn parses_url_lists_and_chainlist_exports() {
+    let list = ImportedList::parse(
+        r#"{
+  "name": "Ethereum Mainnet",
+  "rpc": [
+    "https://mainnet.infura.io/v3/${INFURA_API_KEY}",
+    "https://rpc.example.org/",
+    "wss://rpc.example.org/ws"
+  ]
+}
+http://192.0.2.1:8545
+"#,
+    );
+
+    let endpoints: Vec<&str> = list
+        .candidates()
+        .iter()
+        .filter_map(|c| c.endpoint.as_deref())
+        .collect();
+    assert_eq!(
+        endpoints,
+        ["https://rpc.example.org", "wss://rpc.example.org/ws", "http://192.0.2.1:8545"]
+    );
+    assert_eq!(addresses(&list), [("rpc.example.org", 443), ("rpc.example.org", 443), ("192.0.2.1", 8545)]);
+}
+
+#[test]
+fn keeps_ipv6_url_brackets() {
+    let list = ImportedList::parse(
+        "http://[2001:db8::1]\n         see (wss://[2001:db8::2]:8546/ws).\n         [http://[2001:db8::3]:8545/]\n",
+    );
+
+    let endpoints: Vec<&str> = list.candidates().iter().filter_map(|c| c.endpoint.as_deref()).collect();
+    assert_eq!(
+        endpoints,
+        ["http://[2001:db8::1]", "wss://[2001:db8::2]:8546/ws", "http://[2001:db8::3]:8545"]
+    );
+    assert_eq!(addresses(&list)[0], ("[2001:db8::1]", 80));
+    assert_eq!(list.skipped, 0);
+}
+
+#[test]
+fn counts_lines_without_a_candidate() {
+    let list = ImportedList::parse(
+        "https://mainnet.infura.io/v3/${INFURA_API_KEY}\n         ftp://203.0.113.1/\n         http://203.0.113.1:8545 ftp://203.0.113.2/\n         bad_host!:8545\n",
+    );
+    assert_eq!(addresses(&list), [("203.0.113.1", 8545)]);
+    assert_eq!(list.skipped, 3);
+
+    let masscan = r#"[
+{ "ip": "198.51.100.1", "ports": [ {"port": 8545, "status": "open"} ] },
+{ "ip": "not-an-ip", "ports": [ {"port": 8545, "status": "open"} ] }
+]"#;
+    let list = ImportedList::parse(masscan);
+    assert_eq!(addresses(&list), [("198.51.100.1", 8545)]);
+    assert_eq!(list.skipped, 1);
+}
+
+#[tokio::test]
+async fn imported_list_is_a_discovery_source() {
+    let list = ImportedList::parse("203.0.113.1:8545\nhttp://203.0.113.1:8545/rpc\n");
+    let discovery = Discovery::new().with_source(list);
+
+    let query = DiscoveryQuery {
+        chain_id: 1,
+        country_code: Some("DE".to_string()),
+        min_results: 1,
+        max_pages: 1,
+        shodan: Default::default(),
+    };
+    let merged = discovery.search(&query).await.unwrap();
+
+    // Same address, but one candidate is an exact URL, so both are kept
+    assert_eq!(merged.candidates.len(), 2);
+    assert_eq!(merged.duplicates, 0);
+    assert_eq!(merged.sources["import"].candidates, 2);
+    assert!(merged.candidates.iter().all(|c| c.sources == ["import"]));
+}
@@ -0,0 +1,84 @@
+use node_finder::chains::registry::ChainRegistry;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Write `content` to a file unique to this test run
+fn write_temp(name: &str, content: &Value) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("node_finder-{}-{}.json", std::process::id(), name));
+    fs::write(&path, content.to_string()).unwrap();
+    path
+}
+
+fn chain(id: u64, genesis_hash: &str) -> Value {
+    json!({
+        "id": id,
+        "name": format!("Chain {}", id),
+        "symbol": "🔗",
+        "default_rpc": "https://rpc.example.org",
+        "genesis_hash": genesis_hash,
+    })
+}
+
+#[test]
+fn shipped_registry_is_valid() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("chains.json");
+    let registry = ChainRegistry::load(&path).unwrap();
+
+    let ids: Vec<u64> = registry.chains().iter().map(|c| c.id).collect();
+    for id in [1, 56, 8453, 137, 42161, 10, 43114] {
+        assert!(ids.contains(&id), "chain {} missing from chains.json", id);
+    }
+    assert!(registry.chains().iter().all(|c| !c.reference_rpcs().is_empty()));
+}
+
+#[test]
+fn rejects_duplicate_ids_and_malformed_hashes() {
+    let genesis = format!("0x{}", "ab".repeat(32));
+
+    let duplicate = write_temp("duplicate", &json!({"chains": [chain(1, &genesis), chain(1, &genesis)]}));
+    let error = ChainRegistry::load(&duplicate).unwrap_err();
+    assert!(error.contains("duplicate chain ID 1"), "{}", error);
+
+    for (name, hash) in [("short", "0xabcd".to_string()), ("unprefixed", "ab".repeat(32)), ("empty", String::new())] {
+        let path = write_temp(name, &json!({"chains": [chain(1, &hash)]}));
+        let error = ChainRegistry::load(&path).unwrap_err();
+        assert!(error.contains("malformed genesis hash"), "{}: {}", name, error);
+        fs::remove_file(path).unwrap();
+    }
+
+    let valid = write_temp("valid", &json!({"chains": [chain(1, &genesis), chain(2, &format!("0x{}", "AB".repeat(32)))]}));
+    assert_eq!(ChainRegistry::load(&valid).unwrap().chains().len(), 2);
+    fs::remove_file(duplicate).unwrap();
+    fs::remove_file(valid).unwrap();
+}
+
+#[test]
+fn imports_chainlist_without_overriding_registry_chains() {
+    let chainlist = write_temp(
+        "chainlist",
+        &json!([
+            {"name": "Ethereum Mainnet", "chainId": 1, "rpc": ["https://other.example.org"]},
+            {"name": "Gnosis", "chainId": 100, "rpc": [
+                "wss://rpc.gnosischain.com/wss",
+                "https://mainnet.infura.io/v3/${INFURA_API_KEY}",
+                {"url": "https://rpc.gnosischain.com", "tracking": "none"},
+                "https://gnosis-rpc.publicnode.com"
+            ]},
+            {"name": "No RPCs", "chainId": 999, "rpc": []},
+        ]),
+    );
+
+    let mut registry = ChainRegistry::builtin();
+    assert_eq!(registry.import_chainlist(&chainlist).unwrap(), 1);
+    fs::remove_file(chainlist).unwrap();
+
+    assert_eq!(registry.get(1).unwrap().default_rpc, "https://eth.llamarpc.com");
+    let gnosis = registry.get(100).unwrap();
+    assert_eq!(gnosis.default_rpc, "https://rpc.gnosischain.com");
+    assert_eq!(gnosis.fallback_rpcs, ["https://gnosis-rpc.publicnode.com"]);
+    assert!(gnosis.genesis_hash.is_empty());
+    assert!(registry.get(999).is_none());
+    // Imported chains are found by ID but not listed in menus
+    assert!(registry.chains().iter().all(|c| c.id != 100));
+}
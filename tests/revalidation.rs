@@ -0,0 +1,63 @@
+mod common;
+
+use common::rpc_node;
+use node_finder::chains::Chain;
+use node_finder::discovery::Discovery;
+use node_finder::revalidation::{RevalidationConfig, Revalidator};
+use node_finder::store::NodeStore;
+use node_finder::{SearchPipeline, ValidatedNode};
+use serde_json::json;
+use std::fs;
+
+const GENESIS: &str = "0x4f1dd23188aab3a76b463e4af801b52b1248ef073c648cbdc4c9333d3da79756";
+
+fn node(url: &str) -> ValidatedNode {
+    serde_json::from_value(json!({
+        "url": url,
+        "latency_ms": 40,
+        "block_number": 1_000,
+        "is_archive": false,
+        "oldest_state_block": null,
+        "client": null,
+        "capabilities": [],
+        "security": null,
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn revalidates_nodes_of_custom_chains() {
+    // Serves as both the reference RPC and the stored node
+    let server = rpc_node(100, GENESIS, 1_000).await;
+    let custom = Chain {
+        id: 100,
+        name: "Gnosis".to_string(),
+        symbol: "🔗".to_string(),
+        default_rpc: server.url.clone(),
+        fallback_rpcs: Vec::new(),
+        genesis_hash: GENESIS.to_string(),
+        archive_probe_heights: vec![1],
+        first_state_block: 0,
+        block_time_ms: 5_000,
+    };
+
+    let path = std::env::temp_dir().join(format!("node_finder-revalidation-{}.json", std::process::id()));
+    let _ = fs::remove_file(&path);
+    let store = NodeStore::open(&path);
+    store.update(|db| db.record_success(&custom, &node(&server.url), None)).await;
+    assert_eq!(store.records(100).await[0].chain.as_ref().map(|c| c.id), Some(100));
+
+    // A fresh store sees the chain only through the record
+    let revalidator = Revalidator::new(
+        SearchPipeline::new(Discovery::new()),
+        NodeStore::open(&path),
+        RevalidationConfig::default(),
+    );
+    let summary = revalidator.run_once().await;
+    assert_eq!((summary.checked, summary.healthy, summary.skipped), (1, 1, 0));
+
+    let record = NodeStore::open(&path).records(100).await.remove(0);
+    assert_eq!(record.checks.len(), 2);
+    assert!(record.is_healthy());
+    fs::remove_file(path).unwrap();
+}
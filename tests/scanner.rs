@@ -0,0 +1,75 @@
+mod common;
+
+use common::MockServer;
+use node_finder::discovery::scanner::{Cidr, PortScanner};
+use serde_json::json;
+use std::net::IpAddr;
+
+fn chain_id_server(chain_id: &'static str) -> impl Fn(&common::RecordedRequest) -> (u16, String) {
+    move |_| (200, json!({"jsonrpc": "2.0", "id": 1, "result": chain_id}).to_string())
+}
+
+fn port(server: &MockServer) -> u16 {
+    server.url.rsplit(':').next().unwrap().parse().unwrap()
+}
+
+#[test]
+fn parses_cidr_ranges() {
+    let range: Cidr = "203.0.113.77/30".parse().unwrap();
+    assert_eq!(range.to_string(), "203.0.113.76/30");
+    // Network and broadcast addresses are skipped
+    let addresses: Vec<IpAddr> = range.addresses().collect();
+    assert_eq!(addresses, ["203.0.113.77".parse::<IpAddr>().unwrap(), "203.0.113.78".parse().unwrap()]);
+
+    let host: Cidr = "198.51.100.7".parse().unwrap();
+    assert_eq!(host.len(), 1);
+    assert_eq!("10.0.0.0/16".parse::<Cidr>().unwrap().len(), 65_534);
+    assert_eq!("2001:db8::/126".parse::<Cidr>().unwrap().len(), 4);
+    assert_eq!("2001:db8::/0".parse::<Cidr>().unwrap().len(), u128::MAX);
+
+    assert!("10.0.0.0/33".parse::<Cidr>().is_err());
+    assert!("not-an-ip/24".parse::<Cidr>().is_err());
+}
+
+#[tokio::test]
+async fn scan_returns_nodes_answering_with_the_chain() {
+    let mainnet = MockServer::start(chain_id_server("0x1")).await;
+    let other_chain = MockServer::start(chain_id_server("0x89")).await;
+    let not_rpc = MockServer::start(|_| (404, "{}".to_string())).await;
+
+    // A port nothing listens on
+    let closed = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().port()
+    };
+
+    let scanner = PortScanner::new(vec!["127.0.0.1".parse().unwrap()]).with_ports(vec![
+        port(&mainnet),
+        port(&other_chain),
+        port(&not_rpc),
+        closed,
+    ]);
+    assert_eq!(scanner.target_count(), 4);
+
+    let (candidates, summary) = scanner.scan(1).await.unwrap();
+
+    assert_eq!(summary.probed, 4);
+    assert_eq!(summary.open, 3);
+    assert_eq!(summary.answered, 2);
+    assert_eq!(summary.other_chain, 1);
+
+    assert_eq!(candidates.len(), 1);
+    assert_eq!(candidates[0].http_url(), mainnet.url);
+    assert_eq!(candidates[0].sources, ["scan"]);
+    // Scanned ports are validated whatever their number
+    assert!(candidates[0].listed);
+
+    let probe = &mainnet.requests()[0];
+    assert_eq!((probe.method.as_str(), probe.path.as_str()), ("POST", "/"));
+}
+
+#[tokio::test]
+async fn scan_refuses_ranges_over_the_limit() {
+    let scanner = PortScanner::new(vec!["10.0.0.0/15".parse().unwrap()]);
+    assert!(scanner.scan(1).await.is_err());
+}
@@ -0,0 +1,128 @@
+mod common;
+
+use common::rpc_node;
+use futures::future::BoxFuture;
+use node_finder::chains::Chain;
+use node_finder::discovery::import::ImportedList;
+use node_finder::discovery::{Candidate, Discovery, DiscoveryQuery, DiscoveryResult, DiscoverySource};
+use node_finder::search::{median, CheckpointVote};
+use node_finder::{Error, NodeType, SearchPipeline, SearchRequest};
+
+const GENESIS: &str = "0x4f1dd23188aab3a76b463e4af801b52b1248ef073c648cbdc4c9333d3da79756";
+
+fn chain(genesis_hash: &str) -> Chain {
+    Chain {
+        id: 100,
+        name: "Gnosis".to_string(),
+        symbol: "🔗".to_string(),
+        // Nothing listens here, so a search that got this far would fail differently
+        default_rpc: "http://127.0.0.1:9".to_string(),
+        fallback_rpcs: Vec::new(),
+        genesis_hash: genesis_hash.to_string(),
+        archive_probe_heights: vec![1],
+        first_state_block: 0,
+        block_time_ms: 5_000,
+    }
+}
+
+#[tokio::test]
+async fn refuses_chains_without_a_pinned_genesis() {
+    let pipeline = SearchPipeline::new(Discovery::new());
+    let request = SearchRequest::new(chain(""), NodeType::Full);
+
+    let result = pipeline.search(&request).await;
+    assert!(matches!(result, Err(Error::GenesisUnpinned { chain_id: 100 })));
+}
+
+#[test]
+fn median_takes_the_middle_height() {
+    assert_eq!(median(&mut []), None);
+    assert_eq!(median(&mut [7]), Some(7));
+    assert_eq!(median(&mut [u64::MAX, 100, 101]), Some(101));
+    // Upper median for an even count
+    assert_eq!(median(&mut [103, 100, 101, 102]), Some(102));
+}
+
+#[tokio::test]
+async fn fallback_height_ignores_one_bogus_candidate() {
+    let honest = rpc_node(100, GENESIS, 1_000).await;
+    let close = rpc_node(100, GENESIS, 1_010).await;
+    let bogus = rpc_node(100, GENESIS, 50_000_000).await;
+    let list = ImportedList::parse(&format!("{}\n{}\n{}\n", honest.url, close.url, bogus.url));
+
+    // No reference RPC answers, so candidate heights decide
+    let pipeline = SearchPipeline::new(Discovery::new().with_source(list));
+    let request = SearchRequest::new(chain(GENESIS), NodeType::Full);
+    let outcome = pipeline.search(&request).await.unwrap();
+
+    assert!(outcome.diagnostics.reference_fallback);
+    assert_eq!(outcome.diagnostics.reference_block, Some(1_010));
+    let mut urls: Vec<&str> = outcome.nodes.iter().map(|n| n.url.as_str()).collect();
+    urls.sort_unstable();
+    let mut expected = [honest.url.as_str(), close.url.as_str()];
+    expected.sort_unstable();
+    assert_eq!(urls, expected);
+    assert_eq!(outcome.diagnostics.rejected("not_synced"), 1);
+}
+
+#[test]
+fn checkpoint_needs_a_strict_majority() {
+    let a = || "0xaa".to_string();
+    let b = || "0xbb".to_string();
+
+    let agreed = CheckpointVote::tally(100, vec![b(), a(), b()]);
+    assert_eq!(agreed.checkpoint().map(|c| c.hash), Some(b()));
+    assert_eq!(CheckpointVote::tally(100, vec![a()]).checkpoint().map(|c| c.block), Some(100));
+
+    // A 1–1 split must not fall back to whichever hash sorts last
+    assert_eq!(CheckpointVote::tally(100, vec![a(), b()]), CheckpointVote::Disputed { block: 100 });
+    assert_eq!(
+        CheckpointVote::tally(100, vec![a(), a(), b(), b()]),
+        CheckpointVote::Disputed { block: 100 }
+    );
+    assert_eq!(CheckpointVote::tally(100, Vec::new()), CheckpointVote::Unavailable);
+}
+
+/// A source that cannot filter by chain, like Censys, FOFA or ZoomEye
+struct AnyChainSource(Vec<Candidate>);
+
+impl DiscoverySource for AnyChainSource {
+    fn name(&self) -> &'static str {
+        "anychain"
+    }
+
+    fn search<'a>(&'a self, _query: &'a DiscoveryQuery) -> BoxFuture<'a, Result<DiscoveryResult, Error>> {
+        let candidates = self.0.clone();
+        Box::pin(async move {
+            Ok(DiscoveryResult {
+                total: candidates.len() as u64,
+                candidates,
+                pages_fetched: 1,
+            })
+        })
+    }
+}
+
+fn local_candidate(url: &str) -> Candidate {
+    let port = url.rsplit(':').next().unwrap().parse().unwrap();
+    let mut candidate = Candidate::new("anychain", "127.0.0.1".to_string(), port);
+    candidate.listed = true;
+    candidate
+}
+
+#[tokio::test]
+async fn other_chains_are_dropped_before_taking_a_validation_slot() {
+    let ours = rpc_node(100, GENESIS, 1_000).await;
+    let mainnet = rpc_node(1, GENESIS, 1_000).await;
+    let source = AnyChainSource(vec![local_candidate(&mainnet.url), local_candidate(&ours.url)]);
+
+    let pipeline = SearchPipeline::new(Discovery::new().with_source(source));
+    let request = SearchRequest::new(chain(GENESIS), NodeType::Full);
+    let outcome = pipeline.search(&request).await.unwrap();
+
+    assert_eq!(outcome.nodes.len(), 1);
+    assert_eq!(outcome.nodes[0].url, ours.url);
+    assert_eq!(outcome.diagnostics.rejected("chain_id_mismatch"), 1);
+    // Only the pre-check reached the other chain's node
+    assert_eq!(mainnet.requests().len(), 1);
+}
@@ -0,0 +1,69 @@
+use node_finder::validator::security::{SecurityAudit, MINER_PROBE};
+use node_finder::validator::RpcResponse;
+use serde_json::{json, Value};
+
+fn ok(result: Value) -> RpcResponse {
+    serde_json::from_value(json!({"jsonrpc": "2.0", "id": 1, "result": result})).unwrap()
+}
+
+fn err(code: i64, message: &str) -> RpcResponse {
+    serde_json::from_value(json!({"jsonrpc": "2.0", "id": 1, "error": {"code": code, "message": message}})).unwrap()
+}
+
+const ACCOUNT: &str = "0x0000000000000000000000000000000000000001";
+
+#[test]
+fn listed_accounts_alone_are_not_flagged() {
+    let mut audit = SecurityAudit::default();
+    audit.record("eth_accounts", &ok(json!([ACCOUNT])));
+    audit.record("personal_listWallets", &ok(json!([{"url": "keystore://a", "status": "Locked"}])));
+    assert_eq!(audit.listed_accounts, 1);
+    assert_eq!(audit.unlocked_accounts, 0);
+    assert_eq!(audit.flags(), ["personal"]);
+}
+
+#[test]
+fn counts_unlocked_wallets() {
+    let mut audit = SecurityAudit::default();
+    audit.record(
+        "personal_listWallets",
+        &ok(json!([
+            {"url": "keystore://a", "status": "Locked", "accounts": [{"address": ACCOUNT}]},
+            {"url": "keystore://b", "status": "Unlocked", "accounts": [{"address": ACCOUNT}, {"address": ACCOUNT}]},
+        ])),
+    );
+    assert_eq!(audit.unlocked_accounts, 2);
+    assert_eq!(audit.flags(), ["unlocked accounts", "personal"]);
+}
+
+#[test]
+fn detects_namespaces_without_rpc_modules() {
+    let mut audit = SecurityAudit::default();
+    audit.record(MINER_PROBE, &err(-32602, "missing value for required argument 0"));
+    audit.record("admin_nodeInfo", &ok(json!({"name": "Geth/v1.13.0"})));
+    assert_eq!(audit.flags(), ["admin", "miner"]);
+
+    let mut closed = SecurityAudit::default();
+    closed.record(MINER_PROBE, &err(-32601, "the method miner_setExtra does not exist/is not available"));
+    closed.record("admin_nodeInfo", &err(-32601, "the method admin_nodeInfo does not exist/is not available"));
+    closed.record("rpc_modules", &ok(json!({"eth": "1.0", "net": "1.0"})));
+    assert!(!closed.is_exposed());
+
+    let mut listed = SecurityAudit::default();
+    listed.record("rpc_modules", &ok(json!({"eth": "1.0", "miner": "1.0", "personal": "1.0"})));
+    assert_eq!(listed.flags(), ["personal", "miner"]);
+}
+
+#[test]
+fn miner_probe_needs_an_argument_error() {
+    for (code, message) in [
+        (-32000, "method not allowed"),
+        (-32005, "rate limit exceeded"),
+        (-32602, "miner_setExtra is not whitelisted"),
+        (-32603, "internal error"),
+    ] {
+        let mut audit = SecurityAudit::default();
+        audit.record(MINER_PROBE, &err(code, message));
+        assert!(!audit.miner, "{}", message);
+    }
+}
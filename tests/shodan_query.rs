@@ -0,0 +1,58 @@
+use node_finder::shodan::ShodanQuery;
+
+#[test]
+fn default_query_matches_hex_and_decimal_chain_ids() {
+    let query = ShodanQuery::default().build(137, Some("DE"));
+    assert_eq!(
+        query,
+        "port:8545,8546,443 (\"Chain Id: 0x89\" OR \"Chain Id: 137\") country:DE"
+    );
+}
+
+#[test]
+fn filters_widen_and_narrow_the_query() {
+    let filters = ShodanQuery::parse(
+        "port:8547,8545 product:Nethermind product:\"Hyperledger Besu\" asn:24940 org:\"Hetzner Online GmbH\" \"jsonrpc\"",
+    )
+    .unwrap();
+
+    assert_eq!(filters.ports, [8547, 8545]);
+    assert_eq!(filters.products, ["Nethermind", "Hyperledger Besu"]);
+    assert_eq!(filters.asns, ["AS24940"]);
+    assert_eq!(filters.orgs, ["Hetzner Online GmbH"]);
+    assert_eq!(filters.banners, ["jsonrpc"]);
+
+    assert_eq!(
+        filters.build(1, None),
+        "port:8545,8546,443,8547 (\"Chain Id: 0x1\" OR \"Chain Id: 1\" OR product:\"Nethermind\" \
+         OR product:\"Hyperledger Besu\" OR \"jsonrpc\") asn:AS24940 org:\"Hetzner Online GmbH\""
+    );
+    assert!(filters.requests_port(8547));
+    assert!(!filters.requests_port(8546));
+
+    // Displayed in the syntax it was parsed from
+    assert_eq!(ShodanQuery::parse(&filters.to_string()).unwrap(), filters);
+}
+
+#[test]
+fn raw_queries_replace_the_generated_one() {
+    let filters = ShodanQuery::parse("raw port:9650 \"Chain Id: {chain_id_hex}\" avalanche {chain_id}").unwrap();
+    assert_eq!(
+        filters.build(43114, Some("US")),
+        "port:9650 \"Chain Id: 0xa86a\" avalanche 43114 country:US"
+    );
+    // Every port of a raw query was asked for
+    assert!(filters.requests_port(9650));
+
+    let with_country = ShodanQuery::parse("raw \"Chain Id\" country:FI").unwrap();
+    assert_eq!(with_country.build(1, Some("US")), "\"Chain Id\" country:FI");
+}
+
+#[test]
+fn rejects_unsupported_filters() {
+    assert!(ShodanQuery::parse("http.title:geth").is_err());
+    assert!(ShodanQuery::parse("port:http").is_err());
+    assert!(ShodanQuery::parse("asn:amazon").is_err());
+    assert!(ShodanQuery::parse("\"unbalanced").is_err());
+    assert!(ShodanQuery::parse("").unwrap().is_empty());
+}
@@ -0,0 +1,93 @@
+use node_finder::chains::{get_chain_by_id, Chain};
+use node_finder::store::{NodeStore, MAX_CHECK_HISTORY};
+use node_finder::{Error, ValidatedNode};
+use serde_json::json;
+use std::fs;
+use std::path::PathBuf;
+
+/// Database path unique to this test, with leftovers from earlier runs removed
+fn db_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("node_finder-store-{}-{}.json", std::process::id(), name));
+    for suffix in ["", ".tmp", ".bad"] {
+        let _ = fs::remove_file(format!("{}{}", path.display(), suffix));
+    }
+    path
+}
+
+fn ethereum() -> Chain {
+    get_chain_by_id(1).unwrap()
+}
+
+fn node(url: &str, latency_ms: u64) -> ValidatedNode {
+    serde_json::from_value(json!({
+        "url": url,
+        "latency_ms": latency_ms,
+        "block_number": 19_000_000,
+        "is_archive": false,
+        "oldest_state_block": null,
+        "client": null,
+        "capabilities": [],
+        "security": null,
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn records_survive_a_reopen() {
+    let path = db_path("roundtrip");
+    let store = NodeStore::open(&path);
+    store.update(|db| db.record_success(&ethereum(), &node("http://10.0.0.1:8545", 40), Some("DE"))).await;
+    store.update(|db| db.record_failure(1, "http://10.0.0.1:8545", &Error::Timeout)).await;
+    // Nodes never seen healthy are not stored
+    store.update(|db| db.record_failure(1, "http://10.0.0.2:8545", &Error::Timeout)).await;
+
+    let reopened = NodeStore::open(&path);
+    let records = reopened.records(1).await;
+    assert_eq!(records.len(), 1);
+    let record = &records[0];
+    assert_eq!(record.country_code.as_deref(), Some("DE"));
+    // The registry resolves Ethereum, so the chain is not copied onto the record
+    assert!(record.chain.is_none());
+    assert_eq!(record.consecutive_failures, 1);
+    assert_eq!(record.checks.len(), 2);
+    assert_eq!(record.checks[1].failure.as_deref(), Some("timeout"));
+    assert_eq!(record.success_rate(), 0.5);
+    assert!(!PathBuf::from(format!("{}.tmp", path.display())).exists());
+    fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn trims_history_and_removes_evicted_nodes() {
+    let path = db_path("eviction");
+    let store = NodeStore::open(&path);
+    let url = "http://10.0.0.1:8545";
+    for latency in 0..MAX_CHECK_HISTORY as u64 + 5 {
+        store.update(|db| db.record_success(&ethereum(), &node(url, latency), None)).await;
+    }
+
+    let record = store.records(1).await.remove(0);
+    assert_eq!(record.checks.len(), MAX_CHECK_HISTORY);
+    assert_eq!(record.checks[0].latency_ms, Some(5));
+
+    let removed = store.update(|db| db.remove(1, url)).await;
+    assert!(removed.is_some());
+    assert!(NodeStore::open(&path).all_records().await.is_empty());
+    fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn moves_a_corrupt_file_aside() {
+    let path = db_path("corrupt");
+    fs::write(&path, "{\"chains\": {\"1\": ").unwrap();
+
+    let store = NodeStore::open(&path);
+    assert!(store.all_records().await.is_empty());
+    let bad = PathBuf::from(format!("{}.bad", path.display()));
+    assert_eq!(fs::read_to_string(&bad).unwrap(), "{\"chains\": {\"1\": ");
+
+    store.update(|db| db.record_success(&ethereum(), &node("http://10.0.0.1:8545", 40), None)).await;
+    assert_eq!(NodeStore::open(&path).records(1).await.len(), 1);
+    assert!(bad.exists());
+    fs::remove_file(path).unwrap();
+    fs::remove_file(bad).unwrap();
+}
@@ -0,0 +1,28 @@
+use node_finder::validator::tls::{parse_cert_time, TlsInfo};
+
+#[test]
+fn parses_certificate_times() {
+    assert_eq!(parse_cert_time("19700101000000Z"), Some(0));
+    assert_eq!(parse_cert_time("20000301000000Z"), Some(951_868_800));
+    assert_eq!(parse_cert_time("20240229120000Z"), Some(1_709_208_000));
+    assert_eq!(parse_cert_time("20250315235959Z"), Some(1_742_083_199));
+}
+
+#[test]
+fn rejects_malformed_times() {
+    for input in ["20250315235959", "2025031523595Z", "2025-03-15T23:5Z", "19691231235959Z", "20251315000000Z", "20250300000000Z", ""] {
+        assert_eq!(parse_cert_time(input), None, "{:?}", input);
+    }
+}
+
+#[test]
+fn expiry_counts_down_to_zero() {
+    let tls = TlsInfo {
+        valid: true,
+        expires_at: Some(1_000),
+        issuer: None,
+    };
+    assert_eq!(tls.expires_in(400), Some(600));
+    assert_eq!(tls.expires_in(5_000), Some(0));
+    assert_eq!(TlsInfo::default().expires_in(0), None);
+}
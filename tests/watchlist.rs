@@ -0,0 +1,27 @@
+use node_finder::config::{WatchStatus, WatchedNode};
+
+fn watched(chain_id: u64, url: &str) -> WatchedNode {
+    WatchedNode {
+        url: url.to_string(),
+        chain_id,
+        added_at: 0,
+        status: WatchStatus::Up,
+        status_since: 0,
+        last_checked: 0,
+    }
+}
+
+#[test]
+fn keys_identify_chain_and_url() {
+    let node = watched(1, "http://203.0.113.7:8545");
+    assert_eq!(node.key(), watched(1, "http://203.0.113.7:8545").key());
+    assert_ne!(node.key(), watched(56, "http://203.0.113.7:8545").key());
+    assert_ne!(node.key(), watched(1, "http://203.0.113.8:8545").key());
+}
+
+#[test]
+fn keys_fit_in_callback_data() {
+    let node = watched(u64::MAX, &format!("https://{}.example.com/rpc", "a".repeat(200)));
+    assert!(format!("unwatch:{}", node.key()).len() <= 64);
+    assert_eq!(node.key().split(':').count(), 2);
+}
@@ -0,0 +1,60 @@
+use futures::{SinkExt, StreamExt};
+use node_finder::validator::session::WsSession;
+use node_finder::validator::RpcRequest;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::Message;
+
+/// WebSocket node that waits for `expected` requests, then answers them in
+/// reverse order with a notification and a stray response mixed in
+async fn reordering_node(expected: usize) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+
+        let mut requests = Vec::new();
+        while requests.len() < expected {
+            let Some(Ok(Message::Text(text))) = ws.next().await else {
+                return;
+            };
+            requests.push(serde_json::from_str::<Value>(&text).unwrap());
+        }
+
+        let notification = json!({"jsonrpc": "2.0", "method": "eth_subscription", "params": {"subscription": "0x1", "result": {"number": "0x10"}}});
+        ws.send(Message::Text(notification.to_string())).await.unwrap();
+        ws.send(Message::Text(json!({"jsonrpc": "2.0", "id": 999, "result": "0xdead"}).to_string())).await.unwrap();
+        for request in requests.iter().rev() {
+            let response = json!({"jsonrpc": "2.0", "id": request["id"], "result": request["method"]});
+            ws.send(Message::Text(response.to_string())).await.unwrap();
+        }
+        // Keep the connection open until the client closes it
+        while let Some(Ok(_)) = ws.next().await {}
+    });
+    url
+}
+
+#[tokio::test]
+async fn matches_pipelined_responses_by_id() {
+    let url = reordering_node(3).await;
+    let mut session = WsSession::connect(&url).await.unwrap();
+
+    let requests: Vec<RpcRequest> = ["eth_chainId", "eth_getBlockByNumber", "eth_blockNumber"]
+        .iter()
+        .map(|method| RpcRequest::new(method, vec![]))
+        .collect();
+    let results: Vec<Value> = session
+        .call_many(&requests)
+        .await
+        .into_iter()
+        .map(|r| r.unwrap().result.unwrap())
+        .collect();
+    assert_eq!(results, [json!("eth_chainId"), json!("eth_getBlockByNumber"), json!("eth_blockNumber")]);
+
+    // The notification that arrived mid-batch was kept for the subscription check
+    let notification = session.next_notification(Duration::from_millis(100)).await.unwrap();
+    assert_eq!(notification["params"]["result"]["number"], "0x10");
+    session.close().await;
+}
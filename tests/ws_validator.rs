@@ -0,0 +1,109 @@
+mod common;
+
+use common::ws_server;
+use node_finder::validator::ws::WsValidator;
+use node_finder::ValidatedNode;
+use serde_json::{json, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn node(url: &str, block_number: u64) -> ValidatedNode {
+    serde_json::from_value(json!({
+        "url": url,
+        "latency_ms": 40,
+        "block_number": block_number,
+        "is_archive": false,
+        "oldest_state_block": null,
+        "client": null,
+        "capabilities": [],
+        "security": null,
+    }))
+    .unwrap()
+}
+
+/// A node that confirms `eth_subscribe`, then streams a header for another
+/// subscription followed by `header_number` for ours
+async fn streaming_node(header_number: u64) -> String {
+    ws_server(move |request| {
+        let id = &request["id"];
+        match request["method"].as_str() {
+            Some("eth_subscribe") => {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+                let header = |subscription: &str, number: u64| {
+                    json!({"jsonrpc": "2.0", "method": "eth_subscription", "params": {
+                        "subscription": subscription,
+                        "result": {"number": format!("0x{:x}", number), "timestamp": format!("0x{:x}", now)},
+                    }})
+                };
+                vec![
+                    json!({"jsonrpc": "2.0", "id": id, "result": "0xabc"}),
+                    header("0xother", 1),
+                    header("0xabc", header_number),
+                ]
+            }
+            _ => vec![json!({"jsonrpc": "2.0", "id": id, "result": true})],
+        }
+    })
+    .await
+}
+
+#[tokio::test]
+async fn accepts_nodes_streaming_current_headers() {
+    let url = streaming_node(1_001).await;
+    let checked = WsValidator::new().check_subscription(node(&url, 1_000), 1_000, 1_000, 50).await;
+
+    assert_eq!(checked.supports_subscriptions, Some(true));
+    // The header's timestamp is whole seconds, so the delay is under a second
+    assert!(checked.header_delay_ms.is_some_and(|ms| ms < 2_000));
+}
+
+#[tokio::test]
+async fn rejects_stale_or_missing_headers() {
+    let stale = streaming_node(10).await;
+    let checked = WsValidator::new().check_subscription(node(&stale, 1_000), 1_000, 1_000, 50).await;
+    assert_eq!(checked.supports_subscriptions, Some(false));
+    assert_eq!(checked.header_delay_ms, None);
+
+    let unsupported = ws_server(|request: &Value| {
+        vec![json!({"jsonrpc": "2.0", "id": request["id"], "error": {"code": -32601, "message": "notifications not supported"}})]
+    })
+    .await;
+    let checked = WsValidator::new().check_subscription(node(&unsupported, 1_000), 1_000, 1_000, 50).await;
+    assert_eq!(checked.supports_subscriptions, Some(false));
+}
+
+#[tokio::test]
+async fn survives_absurd_header_timestamps() {
+    let url = ws_server(|request: &Value| {
+        let id = &request["id"];
+        match request["method"].as_str() {
+            Some("eth_subscribe") => vec![
+                json!({"jsonrpc": "2.0", "id": id, "result": "0xabc"}),
+                json!({"jsonrpc": "2.0", "method": "eth_subscription", "params": {
+                    "subscription": "0xabc",
+                    "result": {"number": "0x3e8", "timestamp": "0xffffffffffffffff"},
+                }}),
+            ],
+            _ => vec![json!({"jsonrpc": "2.0", "id": id, "result": true})],
+        }
+    })
+    .await;
+    let checked = WsValidator::new().check_subscription(node(&url, 1_000), 1_000, 1_000, 50).await;
+    assert_eq!(checked.supports_subscriptions, Some(true));
+    assert_eq!(checked.header_delay_ms, Some(0));
+}
+
+#[tokio::test]
+async fn finds_the_first_answering_websocket_endpoint() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let closed = format!("ws://{}", listener.local_addr().unwrap());
+    drop(listener);
+    let chain_id = |request: &Value| vec![json!({"jsonrpc": "2.0", "id": request["id"], "result": "0x1"})];
+    let first = ws_server(chain_id).await;
+    let second = ws_server(chain_id).await;
+
+    let validator = WsValidator::new();
+    let urls = [closed.clone(), format!("{}/ws", first), second];
+    assert_eq!(validator.find_endpoint(&urls).await.unwrap(), urls[1]);
+    assert!(validator.find_endpoint(&[closed]).await.is_err());
+    assert!(validator.find_endpoint(&[]).await.is_err());
+}